@@ -0,0 +1,49 @@
+use alloy::primitives::{address, Address};
+use alloy::providers::MULTICALL3_ADDRESS;
+
+/// Multicall3 address on zkSync Era, where the canonical deployment isn't possible.
+const ZKSYNC_MULTICALL3_ADDRESS: Address = address!("F9cda624FBC7e059355ce98a31693d299FACd963");
+
+/// Chains with Multicall3 deployed at the canonical [`MULTICALL3_ADDRESS`].
+const CANONICAL_CHAINS: &[u64] = &[
+    1,        // Ethereum
+    10,       // Optimism
+    25,       // Cronos
+    56,       // BNB Smart Chain
+    100,      // Gnosis
+    130,      // Unichain
+    137,      // Polygon
+    146,      // Sonic
+    250,      // Fantom
+    1101,     // Polygon zkEVM
+    5000,     // Mantle
+    8453,     // Base
+    17000,    // Holesky
+    34443,    // Mode
+    42161,    // Arbitrum One
+    42220,    // Celo
+    43114,    // Avalanche C-Chain
+    59144,    // Linea
+    80094,    // Berachain
+    81457,    // Blast
+    84532,    // Base Sepolia
+    421614,   // Arbitrum Sepolia
+    534352,   // Scroll
+    560048,   // Hoodi
+    7777777,  // Zora
+    11155111, // Sepolia
+    11155420, // Optimism Sepolia
+];
+
+/// Returns the known Multicall3 address for a chain, if any.
+///
+/// Most chains use the canonical [`MULTICALL3_ADDRESS`], with exceptions such as zkSync Era.
+/// Returns `None` for chains missing from the built-in registry.
+pub fn known_multicall_address(chain_id: u64) -> Option<Address> {
+    match chain_id {
+        // zkSync Era mainnet and Sepolia
+        300 | 324 => Some(ZKSYNC_MULTICALL3_ADDRESS),
+        id if CANONICAL_CHAINS.contains(&id) => Some(MULTICALL3_ADDRESS),
+        _ => None,
+    }
+}
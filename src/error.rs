@@ -0,0 +1,161 @@
+use alloy::{
+    dyn_abi,
+    json_abi::parser,
+    primitives::{hex, Address, Bytes, B256, U256},
+    providers::MulticallError,
+    transports::{RpcError, TransportError, TransportErrorKind},
+};
+use std::time::Duration;
+use thiserror::Error;
+
+use crate::CallOutcome;
+
+/// Errors that can occur when using a [`DynamicMulticallBuilder`](crate::DynamicMulticallBuilder)
+/// or constructing a [`DynCallItem`](crate::DynCallItem).
+///
+/// Construction and validation failures get their own variants, while failures of the multicall
+/// itself are wrapped in [`DynMulticallError::Multicall`].
+#[derive(Debug, Error)]
+pub enum DynMulticallError {
+    /// An error from the multicall itself.
+    #[error(transparent)]
+    Multicall(#[from] MulticallError),
+    /// No contract is deployed at the multicall address.
+    #[error("no multicall contract deployed at {address} on chain {chain_id}")]
+    ContractNotDeployed { address: Address, chain_id: u64 },
+    /// The number of params doesn't match the number of function inputs.
+    #[error("expected {expected} params, got {got}")]
+    ParamCountMismatch { expected: usize, got: usize },
+    /// A param doesn't match the type of the corresponding function input.
+    #[error("param {index} expected type {expected}, got {got}")]
+    TypeMismatch {
+        index: usize,
+        expected: String,
+        got: String,
+    },
+    /// The function signature couldn't be parsed.
+    #[error("invalid function signature {signature:?}: {source}")]
+    SignatureParse {
+        signature: String,
+        #[source]
+        source: parser::Error,
+    },
+    /// The return data of a successful call couldn't be decoded with the outputs of its function.
+    #[error(
+        "could not decode the return data of call {idx} to {target} with {signature} ({}): {source}",
+        return_data_prefix(return_data)
+    )]
+    Decode {
+        idx: usize,
+        target: Address,
+        function: String,
+        signature: String,
+        return_data: Bytes,
+        #[source]
+        source: Box<dyn_abi::Error>,
+    },
+    /// The return data of a call is longer than what its function's outputs decode, see
+    /// [`DynamicMulticallBuilder::with_strict_decode`](crate::DynamicMulticallBuilder::with_strict_decode).
+    #[error(
+        "call {idx} to {target} returned {return_data_len} bytes, but {signature} only decodes {decoded_len}"
+    )]
+    OutputLengthMismatch {
+        idx: usize,
+        target: Address,
+        signature: String,
+        return_data_len: usize,
+        decoded_len: usize,
+    },
+    /// The type of a function input couldn't be resolved.
+    #[error("could not resolve input type: {0}")]
+    Resolve(#[from] dyn_abi::Error),
+    /// A call that isn't allowed to fail targets the zero address.
+    #[error("call {idx} targets the zero address")]
+    ZeroTarget { idx: usize },
+    /// Calls have a value, which `aggregate3` can't forward, see
+    /// [`DynamicMulticallBuilder::aggregate3_value`](crate::DynamicMulticallBuilder::aggregate3_value).
+    #[error("calls {indices:?} have a value, which aggregate3 can't forward")]
+    ValueInAggregate3 { indices: Vec<usize> },
+    /// The chunks of a run pinned to a block executed against different blocks, e.g. after a reorg.
+    ///
+    /// Holds the parent hash and timestamp seen by each chunk, in call order.
+    #[error("chunks pinned to block {block} executed against different blocks")]
+    Consistency {
+        block: u64,
+        parent_hashes: Vec<B256>,
+        timestamps: Vec<u64>,
+    },
+    /// The results of the chunks don't cover each call exactly once, with the index of the first
+    /// call left without a result, given several, or out of range.
+    #[error("chunk results of call {idx} couldn't be reassembled")]
+    ChunkReassembly { idx: usize },
+    /// A chunk limit of zero was given.
+    #[error("chunk size must be greater than zero")]
+    EmptyChunkSize,
+    /// A maximum concurrency of zero was given.
+    #[error("concurrency must be greater than zero")]
+    ZeroConcurrency,
+    /// The whole batch reverted, with the outcome of each call re-executed on its own, see
+    /// [`DynamicMulticallBuilder::with_revert_fallback`](crate::DynamicMulticallBuilder::with_revert_fallback).
+    #[error(
+        "multicall reverted, {} of {} calls revert on their own",
+        .outcomes.iter().filter(|o| o.revert_data.is_some()).count(),
+        .outcomes.len()
+    )]
+    BatchReverted {
+        revert_data: Bytes,
+        outcomes: Vec<CallOutcome>,
+    },
+    /// A block number returned by the multicall contract doesn't fit a `u64`.
+    #[error("block number {0} overflows u64")]
+    BlockNumberOverflow(U256),
+    /// The sum of the call values overflows a `U256`.
+    #[error("sum of call values overflows U256")]
+    ValueOverflow,
+    /// Merged state overrides give an account both a `state` and a `state_diff` override.
+    #[error("conflicting state and state_diff overrides for {address}")]
+    OverrideConflict { address: Address },
+    /// The execution didn't complete within the timeout set with
+    /// [`DynamicMulticallBuilder::with_timeout`](crate::DynamicMulticallBuilder::with_timeout).
+    #[error("multicall timed out after {0:?}")]
+    Timeout(Duration),
+}
+
+/// A request that didn't complete within the timeout set with
+/// [`DynamicMulticallBuilder::with_call_timeout`](crate::DynamicMulticallBuilder::with_call_timeout).
+///
+/// Returned inside a [`TransportErrorKind::Custom`] transport error, see
+/// [`CallTimeout::is_call_timeout`].
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+#[error("request timed out after {0:?}")]
+pub struct CallTimeout(pub Duration);
+
+impl CallTimeout {
+    /// Returns the timeout if the transport error is a [`CallTimeout`].
+    pub fn from_transport_error(err: &TransportError) -> Option<Self> {
+        match err {
+            RpcError::Transport(TransportErrorKind::Custom(err)) => err.downcast_ref().copied(),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the transport error is a [`CallTimeout`].
+    pub fn is_call_timeout(err: &TransportError) -> bool {
+        Self::from_transport_error(err).is_some()
+    }
+}
+
+/// Maximum number of return data bytes shown in a [`DynMulticallError::Decode`] message.
+const RETURN_DATA_PREFIX_LEN: usize = 32;
+
+/// Formats the first bytes of the return data as hex, e.g. `0x1234…, 64 bytes`.
+fn return_data_prefix(return_data: &Bytes) -> String {
+    match return_data.get(..RETURN_DATA_PREFIX_LEN) {
+        Some(prefix) if return_data.len() > RETURN_DATA_PREFIX_LEN => format!(
+            "{}…, {} bytes",
+            hex::encode_prefixed(prefix),
+            return_data.len()
+        ),
+        _ => format!("{}, {} bytes", return_data, return_data.len()),
+    }
+}
@@ -1,20 +1,57 @@
-use std::fmt::Debug;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    future::Future,
+    hash::{BuildHasher, RandomState},
+    ops::{Index, Range},
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 
-use alloy::dyn_abi::{DynSolValue, FunctionExt, JsonAbiExt};
-use alloy::eips::BlockId;
-use alloy::json_abi::Function;
+use alloy::dyn_abi::{DynSolValue, FunctionExt, JsonAbiExt, Specifier};
+use alloy::eips::{
+    eip2930::{AccessList, AccessListResult, AccessListWithGasUsed},
+    BlockId,
+};
+use alloy::json_abi::{Error as AbiError, Function};
 use alloy::network::{Network, TransactionBuilder};
-use alloy::primitives::{Address, Bytes, U256};
+use alloy::primitives::{hex, Address, Bytes, FixedBytes, TxKind, B256, U256};
 use alloy::providers::{
-    bindings::IMulticall3::{aggregate3Call, Call3},
-    Failure, MulticallError, Provider, Result, MULTICALL3_ADDRESS,
+    bindings::IMulticall3::{
+        self, aggregate3Call, aggregate3ValueCall, aggregateCall, aggregateReturn,
+        blockAndAggregateCall, blockAndAggregateReturn, getBlockNumberCall,
+        getCurrentBlockTimestampCall, getEthBalanceCall, getLastBlockHashCall, tryAggregateCall,
+        tryBlockAndAggregateCall, tryBlockAndAggregateReturn, Call, Call3, Call3Value,
+        Result as MulticallResult,
+    },
+    EthCallParams, Failure, MulticallError, PendingTransactionError, Provider, Result,
+    MULTICALL3_ADDRESS,
 };
-use alloy::rpc::types::{state::StateOverride, TransactionInputKind};
-use alloy::sol_types::SolCall;
-use tracing::trace;
+use alloy::rpc::client::BatchRequest;
+use alloy::rpc::types::{
+    state::{AccountOverride, StateOverride},
+    TransactionInputKind,
+};
+use alloy::sol_types::{Panic, PanicKind, Revert, SolCall, SolError};
+use alloy::transports::{RpcError, TransportError, TransportErrorKind, TransportResult};
+use futures::{future, stream, Stream, StreamExt, TryStreamExt};
+use tracing::{debug, trace, warn};
+
+mod chains;
+pub use chains::known_multicall_address;
+
+mod error;
+pub use error::{CallTimeout, DynMulticallError};
+
+#[cfg(feature = "serde")]
+mod ser;
 
 /// Basic version of [alloy::providers::MulticallBuilder] to allow using multicall within type constraints.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct DynamicMulticallBuilder<P: Provider<N>, N: Network> {
     calls: Vec<DynCallItem>,
     provider: P,
@@ -22,6 +59,27 @@ pub struct DynamicMulticallBuilder<P: Provider<N>, N: Network> {
     state_override: Option<StateOverride>,
     address: Address,
     input_kind: TransactionInputKind,
+    allow_value_in_aggregate3: bool,
+    inject_multicall: bool,
+    from: Option<Address>,
+    call_gas_limit: Option<u64>,
+    gas_price: Option<u128>,
+    max_fee_per_gas: Option<u128>,
+    max_priority_fee_per_gas: Option<u128>,
+    access_list: Option<AccessList>,
+    value: Option<U256>,
+    retry_jitter_seed: Option<u64>,
+    strict_decode: bool,
+    pin_blocks: bool,
+    consistency_retries: Option<usize>,
+    progress: Option<ProgressCallback>,
+    retry: Option<(usize, Duration)>,
+    rate_limit_retries: Option<usize>,
+    timeout: Option<Duration>,
+    call_timeout: Option<Duration>,
+    failure_retries: usize,
+    revert_fallback: bool,
+    errors: Vec<AbiError>,
     _pd: std::marker::PhantomData<N>,
 }
 
@@ -39,394 +97,8368 @@ where
             state_override: None,
             address: MULTICALL3_ADDRESS,
             input_kind: TransactionInputKind::default(),
+            allow_value_in_aggregate3: false,
+            inject_multicall: false,
+            from: None,
+            call_gas_limit: None,
+            gas_price: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            access_list: None,
+            value: None,
+            retry_jitter_seed: None,
+            strict_decode: false,
+            pin_blocks: true,
+            consistency_retries: None,
+            progress: None,
+            retry: None,
+            rate_limit_retries: None,
+            timeout: None,
+            call_timeout: None,
+            failure_retries: 0,
+            revert_fallback: false,
+            errors: Vec::new(),
             _pd: Default::default(),
         }
     }
 
-    /// Adds a [`DynCallItem`] to the builder
-    pub fn add_call(mut self, call: DynCallItem) -> Self {
-        self.calls.push(call);
-
+    /// Instantiate a new [`DynamicMulticallBuilder`] with the given calls
+    pub fn with_calls(provider: P, calls: Vec<DynCallItem>) -> Self {
         Self {
-            calls: self.calls,
-            provider: self.provider,
-            block: self.block,
-            state_override: self.state_override,
-            address: self.address,
-            input_kind: self.input_kind,
-            _pd: Default::default(),
+            calls,
+            ..Self::new(provider)
         }
     }
 
-    /// Call the `aggregate3` function
-    pub async fn aggregate3(&self) -> Result<Vec<Result<Vec<DynSolValue>, Failure>>> {
-        let calls = self
-            .calls
-            .iter()
-            .map(|c| {
-                let encoded_call = c.decoder.abi_encode_input(&c.params).map_err(|err| {
-                    MulticallError::DecodeError(alloy::sol_types::Error::custom(err.to_string()))
-                })?;
+    /// Instantiate a new [`DynamicMulticallBuilder`] using the Multicall3 address of the given
+    /// chain.
+    ///
+    /// Falls back to [`MULTICALL3_ADDRESS`] if the chain isn't in the registry, see
+    /// [`known_multicall_address`].
+    pub fn new_for_chain(provider: P, chain_id: u64) -> Self {
+        Self::new(provider).with_address(multicall_address_for_chain(chain_id))
+    }
 
-                Ok(Call3 {
-                    target: c.target,
-                    callData: encoded_call.into(),
-                    allowFailure: c.allow_failure,
-                })
-            })
-            .collect::<Result<Vec<Call3>, MulticallError>>()?;
+    /// Resolve the Multicall3 address from the chain ID of the provider.
+    ///
+    /// Falls back to [`MULTICALL3_ADDRESS`] if the chain isn't in the registry, see
+    /// [`known_multicall_address`].
+    pub async fn resolve_address(&mut self) -> Result<()> {
+        let chain_id = self
+            .provider
+            .get_chain_id()
+            .await
+            .map_err(MulticallError::TransportError)?;
 
-        let call = aggregate3Call {
-            calls: calls.to_vec(),
-        };
+        self.address = multicall_address_for_chain(chain_id);
+        Ok(())
+    }
 
-        trace!("aggregate3Call: {call:?}",);
+    /// Check that a contract is deployed at the multicall address, at the block set on the builder.
+    ///
+    /// Without a deployment, calls return empty data which fails to decode. This is opt-in to avoid
+    /// an extra request on the happy path.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`DynMulticallError::ContractNotDeployed`] if there's no code at the address.
+    pub async fn verify_deployment(&self) -> Result<(), DynMulticallError> {
+        self.with_deadline(async {
+            let mut get_code = self.provider.get_code_at(self.address);
 
-        let results = self.build_and_call(call, None).await?;
+            if let Some(block) = self.block {
+                get_code = get_code.block_id(block);
+            }
 
-        trace!("aggregate3Call results: {results:?}");
+            let code = get_code.await.map_err(MulticallError::TransportError)?;
 
-        if results.len() != calls.len() {
-            return Err(MulticallError::NoReturnData);
-        }
+            if code.is_empty() {
+                let chain_id = self
+                    .provider
+                    .get_chain_id()
+                    .await
+                    .map_err(MulticallError::TransportError)?;
 
-        let mut decoded_results: Vec<Result<Vec<DynSolValue>, Failure>> =
-            Vec::with_capacity(calls.len());
+                return Err(DynMulticallError::ContractNotDeployed {
+                    address: self.address,
+                    chain_id,
+                });
+            }
 
-        for (idx, result) in results.iter().enumerate() {
-            trace!(
-                idx,
-                ?result,
-                target = %calls[idx].target,
-                calldata = %calls[idx].callData,
-                "Attempting to decode result"
-            );
+            Ok(())
+        })
+        .await
+    }
 
-            let decoded_call_result = match result.success {
-                true => {
-                    // Calls to some contracts with a fallback will return success, but actually return no data.
-                    if result.returnData.is_empty() {
-                        Err(Failure {
-                            idx,
-                            return_data: result.returnData.clone(),
-                        })
-                    } else {
-                        let decoded = self.calls[idx]
-                            .decoder
-                            .abi_decode_output(&result.returnData)
-                            .map_err(|err| {
-                                MulticallError::DecodeError(alloy::sol_types::Error::custom(
-                                    err.to_string(),
-                                ))
-                            })?;
-                        Ok(decoded)
-                    }
-                }
-                false => Err(Failure {
-                    idx,
-                    return_data: result.returnData.clone(),
-                }),
-            };
+    /// Adds a [`DynCallItem`] to the builder
+    pub fn add_call(mut self, call: DynCallItem) -> Self {
+        self.calls.push(call);
+        self
+    }
 
-            decoded_results.push(decoded_call_result);
-        }
+    /// Extends the builder with a sequence of [`DynCallItem`]s
+    pub fn add_calls(mut self, calls: impl IntoIterator<Item = DynCallItem>) -> Self {
+        self.calls.extend(calls);
+        self
+    }
 
-        Ok(decoded_results)
+    /// Appends the calls of `other` after the calls of this builder
+    ///
+    /// Only the calls are taken from `other`: the provider and all settings of this builder are
+    /// kept, e.g. the block, state overrides and multicall address, and those of `other` are
+    /// dropped.
+    pub fn merge<P2: Provider<N>>(mut self, other: DynamicMulticallBuilder<P2, N>) -> Self {
+        self.calls.extend(other.calls);
+        self
     }
 
-    /// Helper fn to build a tx and call the multicall contract
-    async fn build_and_call<M: SolCall>(
-        &self,
-        call_type: M,
-        value: Option<U256>,
-    ) -> Result<M::Return> {
-        let call = call_type.abi_encode();
+    /// Removes the [`DynCallItem`] at `idx`, shifting the following calls to the left
+    ///
+    /// Returns `None` if `idx` is out of bounds.
+    pub fn remove_call(&mut self, idx: usize) -> Option<DynCallItem> {
+        (idx < self.calls.len()).then(|| self.calls.remove(idx))
+    }
 
-        let mut tx = N::TransactionRequest::default()
-            .with_to(self.address)
-            .with_input_kind(Bytes::from_iter(call), self.input_kind);
+    /// Inserts a [`DynCallItem`] at `idx`, shifting the following calls to the right
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `idx` is greater than the number of calls.
+    pub fn insert_call(&mut self, idx: usize, call: DynCallItem) {
+        assert!(
+            idx <= self.calls.len(),
+            "insert_call index {idx} is out of bounds for {} calls",
+            self.calls.len()
+        );
+        self.calls.insert(idx, call);
+    }
 
-        if let Some(value) = value {
-            tx.set_value(value);
-        }
+    /// Removes the duplicate calls, i.e. calls with the same target, calldata, value and function
+    /// signature, outputs included, as an earlier call
+    ///
+    /// Returns the index of the remaining call for each original call, to fan the results back out
+    /// with [`fan_out_results`]. The first call of each set of duplicates is kept with its decoder
+    /// and label, and is only allowed to fail if all of its duplicates are. Calls with the same
+    /// calldata but different outputs are kept, as their results decode differently.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if a call can't be encoded, leaving the calls unchanged.
+    pub fn dedup(&mut self) -> Result<Vec<usize>> {
+        let mut seen = HashMap::new();
+        let mut mapping = Vec::with_capacity(self.calls.len());
+        let mut allow_failures = Vec::new();
 
-        let mut eth_call = self.provider.root().call(tx);
+        for call in &self.calls {
+            let key = (
+                call.target,
+                call.encode_input()?,
+                call.value,
+                call.decoder.full_signature(),
+            );
+            let next_idx = seen.len();
+            let idx = *seen.entry(key).or_insert(next_idx);
 
-        if let Some(block) = self.block {
-            eth_call = eth_call.block(block);
-        }
+            match idx == next_idx {
+                true => allow_failures.push(call.allow_failure),
+                false => allow_failures[idx] &= call.allow_failure,
+            }
 
-        if let Some(overrides) = self.state_override.clone() {
-            eth_call = eth_call.overrides(overrides);
+            mapping.push(idx);
         }
 
-        let res = eth_call.await.map_err(MulticallError::TransportError)?;
+        // A call is the first of its duplicates if it maps to the next remaining index.
+        let mut orig_idx = 0;
+        let mut remaining = 0;
+        self.calls.retain(|_| {
+            let first = mapping[orig_idx] == remaining;
+            orig_idx += 1;
+            remaining += usize::from(first);
+            first
+        });
 
-        M::abi_decode_returns(&res).map_err(MulticallError::DecodeError)
+        for (call, allow_failure) in self.calls.iter_mut().zip(allow_failures) {
+            call.allow_failure = allow_failure;
+        }
+
+        Ok(mapping)
     }
 
-    /// Returns a builder with empty calls.
+    /// Add a call to get the eth balance of an address
     ///
-    /// Retains previously set provider, address, block and state_override settings.
-    pub fn clear(self) -> Self {
-        Self {
-            calls: Vec::new(),
-            provider: self.provider,
-            block: self.block,
-            state_override: self.state_override,
-            address: self.address,
-            input_kind: self.input_kind,
-            _pd: Default::default(),
+    /// The call targets the configured multicall address, so it must be set with
+    /// [`DynamicMulticallBuilder::with_address`] beforehand when using a custom deployment.
+    pub fn add_get_eth_balance(self, address: Address) -> Self {
+        self.add_multicall_call(
+            "function getEthBalance(address addr) external view returns (uint256 balance)",
+            vec![DynSolValue::Address(address)],
+        )
+    }
+
+    /// Add calls to get the eth balance of each address
+    pub fn add_get_eth_balances(mut self, addresses: impl IntoIterator<Item = Address>) -> Self {
+        for address in addresses {
+            self = self.add_get_eth_balance(address);
         }
+        self
     }
 
-    /// Get the number of calls in the builder
-    pub fn len(&self) -> usize {
-        self.calls.len()
+    /// Add a call to get the block hash from a block number
+    pub fn add_get_block_hash(self, number: u64) -> Self {
+        self.add_multicall_call(
+            "function getBlockHash(uint256 blockNumber) external view returns (bytes32 blockHash)",
+            vec![DynSolValue::Uint(U256::from(number), 256)],
+        )
     }
 
-    /// Check if the builder is empty
-    pub fn is_empty(&self) -> bool {
-        self.calls.is_empty()
+    /// Add a call to get the current block number
+    pub fn add_get_block_number(self) -> Self {
+        self.add_multicall_call(
+            "function getBlockNumber() external view returns (uint256 blockNumber)",
+            Vec::new(),
+        )
     }
 
-    /// Set the input kind for this builder
-    pub const fn with_input_kind(mut self, input_kind: TransactionInputKind) -> Self {
-        self.input_kind = input_kind;
-        self
+    /// Add a call to get the coinbase of the current block
+    pub fn add_get_current_block_coinbase(self) -> Self {
+        self.add_multicall_call(
+            "function getCurrentBlockCoinbase() external view returns (address coinbase)",
+            Vec::new(),
+        )
     }
 
-    /// Get the input kind for this builder
-    pub const fn input_kind(&self) -> TransactionInputKind {
-        self.input_kind
+    /// Add a call to get the current block difficulty
+    pub fn add_get_current_block_difficulty(self) -> Self {
+        self.add_multicall_call(
+            "function getCurrentBlockDifficulty() external view returns (uint256 difficulty)",
+            Vec::new(),
+        )
     }
-}
 
-/// An individual multicall call item
-#[derive(Clone)]
-pub struct DynCallItem {
-    target: Address,
-    params: Vec<DynSolValue>,
-    allow_failure: bool,
-    value: U256,
-    decoder: Function,
-}
+    /// Add a call to get the current block gas limit
+    pub fn add_get_current_block_gas_limit(self) -> Self {
+        self.add_multicall_call(
+            "function getCurrentBlockGasLimit() external view returns (uint256 gaslimit)",
+            Vec::new(),
+        )
+    }
 
-impl Debug for DynCallItem {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("CallItem")
-            .field("target", &self.target)
-            .field("allow_failure", &self.allow_failure)
-            .field("value", &self.value)
-            .field("function", &self.decoder.name)
-            .field("params", &self.params)
-            .finish()
+    /// Add a call to get the current block timestamp
+    pub fn add_get_current_block_timestamp(self) -> Self {
+        self.add_multicall_call(
+            "function getCurrentBlockTimestamp() external view returns (uint256 timestamp)",
+            Vec::new(),
+        )
     }
-}
 
-impl DynCallItem {
-    /// Create a new [`DynCallItem`] instance.
-    pub const fn new(
-        target: Address,
-        params: Vec<DynSolValue>,
-        function: Function,
-        allow_failure: bool,
-    ) -> Self {
-        Self {
-            target,
-            params,
-            allow_failure,
-            value: U256::ZERO,
-            decoder: function,
-        }
+    /// Add a call to get the chain id
+    pub fn add_get_chain_id(self) -> Self {
+        self.add_multicall_call(
+            "function getChainId() external view returns (uint256 chainid)",
+            Vec::new(),
+        )
     }
 
-    /// Set whether the call should be allowed to fail or not.
-    pub const fn allow_failure(mut self, allow_failure: bool) -> Self {
-        self.allow_failure = allow_failure;
-        self
+    /// Add a call to get the base fee
+    pub fn add_get_base_fee(self) -> Self {
+        self.add_multicall_call(
+            "function getBasefee() external view returns (uint256 basefee)",
+            Vec::new(),
+        )
     }
 
-    /// Set the value to send with the call.
-    pub const fn value(mut self, value: U256) -> Self {
-        self.value = value;
-        self
+    /// Add a call to get the last block hash
+    pub fn add_get_last_block_hash(self) -> Self {
+        self.add_multicall_call(
+            "function getLastBlockHash() external view returns (bytes32 blockHash)",
+            Vec::new(),
+        )
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use alloy::{primitives::address, sol};
-    use alloy_provider::ProviderBuilder;
+    /// Adds a call to one of the multicall contract's own functions, targeting the configured
+    /// multicall address.
+    fn add_multicall_call(self, signature: &str, params: Vec<DynSolValue>) -> Self {
+        let call = DynCallItem::new(self.address, params, multicall_function(signature), false);
+        self.add_call(call)
+    }
 
-    use super::*;
+    /// Call the `aggregate` function
+    ///
+    /// Requires that all calls succeed, else the whole multicall reverts.
+    ///
+    /// If the multicall reverts, the revert data is returned as a [`MulticallError::CallFailed`].
+    /// Return data that can't be decoded is returned as a [`DynMulticallError::Decode`].
+    ///
+    /// ## Returns
+    ///
+    /// - The block number the multicall was executed at.
+    /// - The decoded return values for each call.
+    pub async fn aggregate(&self) -> Result<(u64, Vec<Vec<DynSolValue>>), DynMulticallError> {
+        self.with_deadline(async {
+            let calls = self.encode_calls()?;
 
-    sol! {
-        #[derive(Debug, PartialEq)]
-        #[sol(rpc, abi)]
-        interface ERC20 {
-            function totalSupply() external view returns (uint256 totalSupply);
-            function balanceOf(address owner) external view returns (uint256 balance);
-            function transfer(address to, uint256 value) external returns (bool);
-        }
+            let call = aggregateCall { calls };
+
+            trace!("aggregateCall: {call:?}");
+
+            let aggregateReturn {
+                blockNumber,
+                returnData,
+            } = self
+                .build_and_call(call, None)
+                .await
+                .map_err(revert_data_to_call_failed)?;
+
+            trace!(%blockNumber, "aggregateCall results: {returnData:?}");
+
+            if returnData.len() != self.calls.len() {
+                return Err(MulticallError::NoReturnData.into());
+            }
+
+            let decoded_results = self
+                .calls
+                .iter()
+                .zip(returnData.iter())
+                .enumerate()
+                .map(|(idx, (call, return_data))| {
+                    call.decoder
+                        .abi_decode_output(return_data)
+                        .map_err(|source| DynMulticallError::Decode {
+                            idx,
+                            target: call.target,
+                            function: call.decoder.name.clone(),
+                            signature: call.decoder.full_signature(),
+                            return_data: return_data.clone(),
+                            source: Box::new(source),
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok((block_number_to_u64(blockNumber)?, decoded_results))
+        })
+        .await
     }
 
-    const FORK_URL: &str = "https://reth-ethereum.ithaca.xyz/rpc";
+    /// Call the `aggregate3` function
+    ///
+    /// `aggregate3` can't forward value, so a [`DynMulticallError::ValueInAggregate3`] with the
+    /// indices of the offending calls is returned if any call
+    /// has a non-zero value, unless [`DynamicMulticallBuilder::allow_value_in_aggregate3`] is set.
+    /// Use [`DynamicMulticallBuilder::aggregate3_value`] for value-bearing calls.
+    ///
+    /// Failed calls are returned as their raw [`Failure`]. Use
+    /// [`DynamicMulticallBuilder::aggregate3_detailed`] to get the decoded revert reason of each
+    /// failure instead.
+    ///
+    /// Returns an empty result without making a request if the builder is empty.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`DynMulticallError::ZeroTarget`] without making a request if a call that isn't
+    /// allowed to fail targets the zero address, which usually comes from an uninitialized address.
+    pub async fn aggregate3(
+        &self,
+    ) -> Result<Vec<Result<Vec<DynSolValue>, Failure>>, DynMulticallError> {
+        self.aggregate3_with_retry_policy(self.input_kind, self.retry)
+            .await
+    }
 
-    #[tokio::test]
-    async fn test_dynamic_multicaller() {
-        let _ = tracing_subscriber::fmt::try_init();
+    /// Call the `aggregate3` function, retrying transient transport errors
+    ///
+    /// Same as [`DynamicMulticallBuilder::aggregate3`] with [`DynamicMulticallBuilder::with_retry`]
+    /// set to `max_retries` and `base_delay` for this call only, replacing the retry policy set on
+    /// the builder if any.
+    pub async fn aggregate3_with_retry(
+        &self,
+        max_retries: usize,
+        base_delay: Duration,
+    ) -> Result<Vec<Result<Vec<DynSolValue>, Failure>>, DynMulticallError> {
+        self.aggregate3_with_retry_policy(self.input_kind, Some((max_retries, base_delay)))
+            .await
+    }
 
-        let weth = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
-        let provider = ProviderBuilder::new().connect_anvil_with_config(|a| a.fork(FORK_URL));
+    /// Calls the `aggregate3` function with the given input kind, retrying each `eth_call` with the
+    /// given policy, see [`DynamicMulticallBuilder::with_retry`].
+    async fn aggregate3_with_retry_policy(
+        &self,
+        input_kind: TransactionInputKind,
+        retry: Option<(usize, Duration)>,
+    ) -> Result<Vec<Result<Vec<DynSolValue>, Failure>>, DynMulticallError> {
+        self.with_deadline(async {
+            check_targets(&self.calls)?;
+            self.check_aggregate3_values()?;
 
-        let total_supply_function = ERC20::abi::functions()
-            .get("totalSupply")
-            .cloned()
-            .unwrap()
-            .first()
-            .unwrap()
-            .clone();
+            let mut results = match self
+                .aggregate3_calls(&self.calls, 0, input_kind, self.block, retry)
+                .await
+            {
+                Ok(results) => results,
+                Err(err) if self.revert_fallback => {
+                    return Err(self.diagnose_revert(err, input_kind, retry).await)
+                }
+                Err(err) => return Err(err),
+            };
 
-        let balance_of_function = ERC20::abi::functions()
-            .get("balanceOf")
-            .cloned()
-            .unwrap()
-            .first()
-            .unwrap()
-            .clone();
+            self.retry_failures(&mut results, input_kind, retry).await;
 
-        let balance_of_call_item = DynCallItem::new(
-            weth,
-            vec![DynSolValue::Address(address!(
-                "d8dA6BF26964aF9D7eEd9e03E53415D37aA96045"
-            ))],
-            balance_of_function,
-            false,
-        );
+            Ok(results)
+        })
+        .await
+    }
 
-        let total_supply_call_item =
-            DynCallItem::new(weth, Vec::new(), total_supply_function, false);
+    /// Call the `aggregate3` function with the given input kind
+    ///
+    /// Same as [`DynamicMulticallBuilder::aggregate3`], including the revert fallback and the
+    /// individual retries of failed calls, but overrides the input kind set with
+    /// [`DynamicMulticallBuilder::with_input_kind`] for this call and its re-executions only.
+    pub async fn aggregate3_with_input_kind(
+        &self,
+        input_kind: TransactionInputKind,
+    ) -> Result<Vec<Result<Vec<DynSolValue>, Failure>>, DynMulticallError> {
+        self.aggregate3_with_retry_policy(input_kind, self.retry)
+            .await
+    }
 
-        let dynamic_multicall = DynamicMulticallBuilder::new(provider.clone())
-            .add_call(balance_of_call_item)
-            .add_call(total_supply_call_item);
+    /// Call the `aggregate3` function, pairing each result with the label of its call
+    ///
+    /// See [`DynCallItem::label`].
+    pub async fn aggregate3_labeled(
+        &self,
+    ) -> Result<Vec<(Option<String>, Result<Vec<DynSolValue>, Failure>)>, DynMulticallError> {
+        let results = self.aggregate3().await?;
 
-        assert_eq!(dynamic_multicall.len(), 2);
+        Ok(self
+            .calls
+            .iter()
+            .map(|c| c.label.clone())
+            .zip(results)
+            .collect())
+    }
 
-        let res = dynamic_multicall.aggregate3().await.unwrap();
+    /// Call the `aggregate3` function, pairing each result with the index of its call in the
+    /// builder
+    ///
+    /// The index matches [`Failure::idx`] for failed calls, so results can be reordered or
+    /// filtered without losing track of their call. Chunked results are reassembled in the
+    /// original call order, so enumerating them gives the same indices.
+    pub async fn aggregate3_indexed(
+        &self,
+    ) -> Result<Vec<(usize, Result<Vec<DynSolValue>, Failure>)>, DynMulticallError> {
+        Ok(self.aggregate3().await?.into_iter().enumerate().collect())
+    }
 
-        assert_eq!(res.len(), 2);
+    /// Call the `aggregate3` function, returning each failed call as a [`DynFailure`] with the
+    /// target, function and decoded revert reason of its call
+    ///
+    /// Revert reasons are decoded with [`DynamicMulticallBuilder::revert_reason`].
+    pub async fn aggregate3_detailed(
+        &self,
+    ) -> Result<Vec<Result<Vec<DynSolValue>, DynFailure>>, DynMulticallError> {
+        let results = self.aggregate3().await?;
+        let mut detailed = Vec::with_capacity(results.len());
 
-        for result in res {
-            let decoded = result.unwrap();
-            assert_eq!(decoded.len(), 1);
+        for (call, result) in self.calls.iter().zip(results) {
+            detailed.push(result.map_err(|failure| DynFailure {
+                reason: Some(self.revert_reason(&failure)),
+                ..DynFailure::new(failure, call)
+            }));
         }
+
+        Ok(detailed)
     }
 
-    #[tokio::test]
-    async fn test_dynamic_multicaller_with_many() {
-        let _ = tracing_subscriber::fmt::try_init();
+    /// Call the `aggregate3` function, keying the decoded outputs of each call by name
+    ///
+    /// See [`named_outputs`].
+    pub async fn aggregate3_named(
+        &self,
+    ) -> Result<Vec<Result<BTreeMap<String, DynSolValue>, Failure>>, DynMulticallError> {
+        let results = self.aggregate3().await?;
 
-        let weth = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
-        let provider = ProviderBuilder::new().connect_anvil_with_config(|a| a.fork(FORK_URL));
+        Ok(self
+            .calls
+            .iter()
+            .zip(results)
+            .map(|(call, result)| result.map(|values| named_outputs(&call.decoder, values)))
+            .collect())
+    }
 
-        let total_supply_function = ERC20::abi::functions()
+    /// Call the `aggregate3` function, decoding the revert reason of each failed call
+    ///
+    /// See [`DynamicMulticallBuilder::revert_reason`].
+    pub async fn simulate(&self) -> Result<Vec<SimResult>, DynMulticallError> {
+        let results = self.aggregate3().await?;
+
+        Ok(results
+            .into_iter()
+            .map(|result| {
+                let revert = result
+                    .as_ref()
+                    .err()
+                    .map(|failure| self.revert_reason(failure));
+                let revert_reason = revert.as_ref().map(ToString::to_string);
+
+                SimResult {
+                    result,
+                    revert_reason,
+                    revert,
+                }
+            })
+            .collect())
+    }
+
+    /// Call the `aggregate3` function once per set of state overrides
+    ///
+    /// Each step is a separate `eth_call` at the same block, with its overrides merged on top of the
+    /// builder's, see [`DynamicMulticallBuilder::merge_state_override`]. Steps don't build on each
+    /// other, so a sequence of state transitions has to repeat the overrides of earlier steps.
+    ///
+    /// ## Returns
+    ///
+    /// The decoded results of every call for each step, in step order.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`DynMulticallError::ValueInAggregate3`] without making a request if any call
+    /// has a value and values aren't allowed in `aggregate3`.
+    pub async fn simulate_bundle(
+        &self,
+        state_sequence: Vec<StateOverride>,
+    ) -> Result<Vec<Vec<Result<Vec<DynSolValue>, Failure>>>, DynMulticallError> {
+        self.with_deadline(async {
+            check_targets(&self.calls)?;
+            self.check_aggregate3_values()?;
+
+            if self.calls.is_empty() {
+                return Ok(vec![Vec::new(); state_sequence.len()]);
+            }
+
+            let call = aggregate3Call {
+                calls: encode_calls3(&self.calls)?,
+            };
+
+            let mut steps = Vec::with_capacity(state_sequence.len());
+
+            for (step, step_override) in state_sequence.into_iter().enumerate() {
+                let mut overrides = self.state_override.clone().unwrap_or_default();
+
+                for (address, account) in step_override {
+                    merge_account_override(
+                        address,
+                        overrides.entry(address).or_default(),
+                        account,
+                    )?;
+                }
+
+                trace!(step, "Executing aggregate3 bundle step");
+
+                let results = self
+                    .build_and_call_with_input_kind(
+                        &call,
+                        None,
+                        self.input_kind,
+                        self.block,
+                        self.inject_multicall_code(Some(overrides)),
+                        self.retry,
+                    )
+                    .await?;
+
+                steps.push(decode_results(&self.calls, results, 0, self.strict_decode)?);
+            }
+
+            Ok(steps)
+        })
+        .await
+    }
+
+    /// Call the `aggregate3` function without decoding the results
+    ///
+    /// Returns the raw `returnData` of each successful call, or a [`Failure`] for each reverted
+    /// call. Useful to decode the results elsewhere, or when a contract returns data that doesn't
+    /// match the declared outputs of its function.
+    pub async fn aggregate3_raw(&self) -> Result<Vec<Result<Bytes, Failure>>, DynMulticallError> {
+        self.with_deadline(async {
+            check_targets(&self.calls)?;
+            self.check_aggregate3_values()?;
+
+            if self.calls.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let results = self
+                .execute_aggregate3(&self.calls, self.input_kind, self.block, self.retry)
+                .await?;
+
+            if results.len() != self.calls.len() {
+                return Err(MulticallError::NoReturnData.into());
+            }
+
+            Ok(results
+                .into_iter()
+                .enumerate()
+                .map(|(idx, result)| match result.success {
+                    true => Ok(result.returnData),
+                    false => Err(Failure {
+                        idx,
+                        return_data: result.returnData,
+                    }),
+                })
+                .collect())
+        })
+        .await
+    }
+
+    /// Call the `aggregate3` function without relying on a Multicall3 deployment
+    ///
+    /// The calls are executed through an `eth_call` without a `to` address, whose init code deploys
+    /// Multicall3, calls `aggregate3` on it and returns the results from the constructor. This
+    /// works on any chain, without state overrides.
+    ///
+    /// The results are returned as contract code, so the encoded results can't exceed the maximum
+    /// contract size (24KiB on most chains).
+    ///
+    /// Calls added with the `add_get_*` helpers target the configured multicall address rather than
+    /// the temporary deployment, so they still require Multicall3 to be deployed there.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`DynMulticallError::ValueInAggregate3`] without making a request if any call has a value, or
+    /// a [`DynMulticallError::ZeroTarget`] like [`DynamicMulticallBuilder::aggregate3`].
+    pub async fn aggregate3_deployless(
+        &self,
+    ) -> Result<Vec<Result<Vec<DynSolValue>, Failure>>, DynMulticallError> {
+        self.with_deadline(async {
+            check_targets(&self.calls)?;
+            check_no_values(&self.calls)?;
+
+            let call = aggregate3Call {
+                calls: encode_calls3(&self.calls)?,
+            };
+
+            trace!("deployless aggregate3Call: {call:?}");
+
+            let mut tx = N::TransactionRequest::default()
+                .with_input_kind(deployless_init_code(&call.abi_encode()), self.input_kind)
+                .with_kind(TxKind::Create);
+
+            self.apply_tx_fields(&mut tx);
+            self.apply_call_fields(&mut tx);
+
+            let res = self
+                .eth_call(tx, self.block, self.call_state_override(), self.retry)
+                .await
+                .map_err(|err| self.call_error(err))?;
+
+            let results =
+                aggregate3Call::abi_decode_returns(&res).map_err(MulticallError::DecodeError)?;
+
+            trace!("deployless aggregate3Call results: {results:?}");
+
+            decode_results(&self.calls, results, 0, self.strict_decode)
+        })
+        .await
+    }
+
+    /// Execute each call as an individual `eth_call`, without the multicall contract
+    ///
+    /// The calls are sent as a single JSON-RPC batch request, for RPCs that block the multicall
+    /// contract but support batching. Results are returned in the same shape as
+    /// [`DynamicMulticallBuilder::aggregate3`], with the block and state overrides applied to each
+    /// call, including the Multicall3 code override if
+    /// [`DynamicMulticallBuilder::with_injected_multicall`] is set, so calls to the multicall
+    /// contract itself succeed. Unlike `aggregate3`, the calls are sent from the address set with
+    /// [`DynamicMulticallBuilder::with_from`] rather than the multicall contract. The batch is
+    /// bounded by [`DynamicMulticallBuilder::with_timeout`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`MulticallError::CallFailed`] with the revert data if a call that isn't allowed to
+    /// fail reverts. Reverts without data are returned with empty revert data.
+    pub async fn execute_as_individual_calls(
+        &self,
+    ) -> Result<Vec<Result<Vec<DynSolValue>, Failure>>, DynMulticallError> {
+        self.with_deadline(async {
+            self.check_aggregate3_values()?;
+
+            let params = self
+                .calls
+                .iter()
+                .map(|c| {
+                    let mut tx = N::TransactionRequest::default()
+                        .with_to(c.target)
+                        .with_input_kind(c.encode_input()?, self.input_kind);
+
+                    self.apply_tx_fields(&mut tx);
+                    self.apply_call_fields(&mut tx);
+
+                    let mut params = EthCallParams::<N>::new(tx);
+
+                    if let Some(block) = self.block {
+                        params = params.with_block(block);
+                    }
+
+                    if let Some(overrides) = self.call_state_override() {
+                        params = params.with_overrides(overrides);
+                    }
+
+                    Ok(params)
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            trace!(calls = params.len(), "sending eth_call batch");
+
+            let responses = self
+                .send_with_policy(self.retry, || async {
+                    let mut batch = BatchRequest::new(self.provider.client());
+
+                    let waiters = params
+                        .iter()
+                        .map(|params| batch.add_call::<_, Bytes>("eth_call", params))
+                        .collect::<TransportResult<Vec<_>>>()?;
+
+                    batch.await?;
+
+                    Ok(future::join_all(waiters).await)
+                })
+                .await
+                .map_err(MulticallError::TransportError)?;
+
+            let mut results = Vec::with_capacity(responses.len());
+
+            for (call, response) in self.calls.iter().zip(responses) {
+                let result = match response {
+                    Ok(return_data) => MulticallResult {
+                        success: true,
+                        returnData: return_data,
+                    },
+                    Err(err) => match execution_revert_data(&err) {
+                        Some(revert_data) if call.allow_failure => MulticallResult {
+                            success: false,
+                            returnData: revert_data,
+                        },
+                        Some(revert_data) => {
+                            return Err(MulticallError::CallFailed(revert_data).into())
+                        }
+                        None => return Err(self.call_error(err).into()),
+                    },
+                };
+
+                results.push(result);
+            }
+
+            decode_results(&self.calls, results, 0, self.strict_decode)
+        })
+        .await
+    }
+
+    /// Call the `aggregate3` function in chunks bounded by `limit`
+    ///
+    /// Each chunk is sent as a separate multicall, one after the other, which keeps large batches
+    /// under node gas and calldata limits. A `usize` limit is a maximum number of calls per chunk,
+    /// see [`ChunkLimit`] to bound chunks by calldata size instead. Results are returned in the
+    /// original call order, and the index of a [`Failure`] refers to the position of the call in
+    /// the builder.
+    ///
+    /// Chunks are separate `eth_call`s, so unless a block is set with
+    /// [`DynamicMulticallBuilder::with_block`], the current block number is fetched first and every
+    /// chunk is pinned to it for a consistent snapshot, see
+    /// [`DynamicMulticallBuilder::without_block_pinning`].
+    ///
+    /// ## Returns
+    ///
+    /// - The block number the chunks were pinned to, if any.
+    /// - The decoded return values for each call.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`DynMulticallError::EmptyChunkSize`] if the limit is zero, or an error if any
+    /// chunk fails.
+    pub async fn aggregate3_chunked(
+        &self,
+        limit: impl Into<ChunkLimit>,
+    ) -> Result<(Option<u64>, Vec<Result<Vec<DynSolValue>, Failure>>), DynMulticallError> {
+        self.with_deadline(async {
+            let chunks = split_chunks(&self.calls, limit.into())?;
+
+            check_targets(&self.calls)?;
+            self.check_aggregate3_values()?;
+
+            self.aggregate3_chunks(&chunks, 1).await
+        })
+        .await
+    }
+
+    /// Call the `aggregate3` function in chunks bounded by `limit`, with up to `max_concurrency`
+    /// chunks in flight at once
+    ///
+    /// Behaves like [`DynamicMulticallBuilder::aggregate3_chunked`], including result ordering and
+    /// block pinning, but dispatches chunks concurrently to cut total latency. Chunks may complete
+    /// in any order, and each result is put back at the index of its call, with an error if any
+    /// call ends up with no result or several. The first failing chunk drops the chunks still in flight.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`DynMulticallError::EmptyChunkSize`] if the limit is zero, a
+    /// [`DynMulticallError::ZeroConcurrency`] if `max_concurrency` is zero, or an error if any
+    /// chunk fails.
+    pub async fn aggregate3_concurrent(
+        &self,
+        limit: impl Into<ChunkLimit>,
+        max_concurrency: usize,
+    ) -> Result<(Option<u64>, Vec<Result<Vec<DynSolValue>, Failure>>), DynMulticallError> {
+        self.with_deadline(async {
+            let chunks = split_chunks(&self.calls, limit.into())?;
+            if max_concurrency == 0 {
+                return Err(DynMulticallError::ZeroConcurrency);
+            }
+
+            check_targets(&self.calls)?;
+            self.check_aggregate3_values()?;
+
+            self.aggregate3_chunks(&chunks, max_concurrency).await
+        })
+        .await
+    }
+
+    /// Call the `aggregate3` function in chunks of up to `initial_chunk_size` calls, adapting the
+    /// chunk size to the node's `eth_call` gas cap
+    ///
+    /// Chunks are executed one after the other and pinned to the same block like in
+    /// [`DynamicMulticallBuilder::aggregate3_chunked`]. When a chunk runs out of gas, i.e. it
+    /// fails with an out of gas error or reverts without any data, it's split in half and
+    /// retried, down to `min_chunk_size` calls. Each successful chunk grows the chunk size back
+    /// by a quarter, up to `initial_chunk_size`. Results are returned in the original call order.
+    ///
+    /// The progress callback set with [`DynamicMulticallBuilder::with_progress`] is invoked after
+    /// each successful chunk, with the total number of chunks estimated from the current chunk
+    /// size.
+    ///
+    /// ## Returns
+    ///
+    /// - The [`AdaptiveStats`] of the execution, including the final chunk size.
+    /// - The decoded return values for each call.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`DynMulticallError::EmptyChunkSize`] if either size is zero, the out of gas
+    /// error if a chunk of `min_chunk_size` calls or a single call still exceeds the gas cap, or
+    /// an error if any chunk fails otherwise.
+    pub async fn aggregate3_adaptive(
+        &self,
+        initial_chunk_size: usize,
+        min_chunk_size: usize,
+    ) -> Result<(AdaptiveStats, Vec<Result<Vec<DynSolValue>, Failure>>), DynMulticallError> {
+        self.with_deadline(async {
+            if initial_chunk_size == 0 || min_chunk_size == 0 {
+                return Err(DynMulticallError::EmptyChunkSize);
+            }
+
+            check_targets(&self.calls)?;
+            self.check_aggregate3_values()?;
+
+            let (block, pinned_block) = self.chunk_block().await?;
+
+            let mut stats = AdaptiveStats {
+                block: pinned_block,
+                final_chunk_size: initial_chunk_size,
+                splits: 0,
+                requests: 0,
+            };
+            let mut results = Vec::with_capacity(self.calls.len());
+            let mut size = initial_chunk_size;
+            let mut start = 0;
+            let mut chunks_completed = 0;
+            let started = Instant::now();
+
+            while start < self.calls.len() {
+                let end = self.calls.len().min(start + size);
+                let chunk = &self.calls[start..end];
+
+                stats.requests += 1;
+
+                match self
+                    .aggregate3_calls(chunk, start, self.input_kind, block, self.retry)
+                    .await
+                {
+                    Ok(chunk_results) => {
+                        results.extend(chunk_results);
+                        start = end;
+                        chunks_completed += 1;
+
+                        stats.final_chunk_size = size;
+                        size = (size + (size / 4).max(1)).min(initial_chunk_size);
+
+                        if let Some(callback) = &self.progress {
+                            report_progress(
+                                callback,
+                                Progress {
+                                    chunks_completed,
+                                    chunks_total: chunks_completed
+                                        + (self.calls.len() - start).div_ceil(size),
+                                    calls_completed: start,
+                                    calls_total: self.calls.len(),
+                                    elapsed: started.elapsed(),
+                                },
+                            );
+                        }
+                    }
+                    Err(err) if is_gas_cap_error(&err) && chunk.len() > min_chunk_size.max(1) => {
+                        size = chunk.len().div_ceil(2).max(min_chunk_size);
+                        stats.splits += 1;
+
+                        trace!(
+                            offset = start,
+                            chunk_len = chunk.len(),
+                            chunk_size = size,
+                            "aggregate3 chunk exceeded the gas cap, splitting"
+                        );
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+
+            Ok((stats, results))
+        })
+        .await
+    }
+
+    /// Stream the results of the `aggregate3` function in chunks bounded by `limit`
+    ///
+    /// Chunks are encoded and executed one at a time as the stream is polled, so a slow consumer
+    /// paces the requests and only one chunk of results is held at once. Chunks are pinned to the
+    /// same block like in [`DynamicMulticallBuilder::aggregate3_chunked`], but the consistency
+    /// check doesn't apply. No task is spawned, so dropping the stream stops the execution.
+    ///
+    /// Validation errors, e.g. a [`DynMulticallError::EmptyChunkSize`], are returned as the first
+    /// item of the stream.
+    pub fn aggregate3_stream(
+        &self,
+        limit: impl Into<ChunkLimit>,
+    ) -> impl Stream<Item = Result<ChunkResults, DynMulticallError>> + '_ {
+        let limit = limit.into();
+
+        stream::once(async move {
+            let chunks = split_chunks(&self.calls, limit)?;
+
+            check_targets(&self.calls)?;
+            self.check_aggregate3_values()?;
+
+            let (block, pinned_block) = self.chunk_block().await?;
+
+            let progress = self.progress_tracker(&chunks, Instant::now());
+
+            Ok::<_, DynMulticallError>(stream::iter(chunks).then(move |(offset, chunk)| {
+                let progress = progress.clone();
+
+                async move {
+                    let results = self
+                        .aggregate3_calls(chunk, offset, self.input_kind, block, self.retry)
+                        .await;
+
+                    if let Some(progress) = progress {
+                        progress.chunk_completed(chunk.len());
+                    }
+
+                    Ok(ChunkResults {
+                        range: offset..offset + chunk.len(),
+                        block: pinned_block,
+                        results: results?,
+                    })
+                }
+            }))
+        })
+        .try_flatten()
+    }
+
+    /// Stream the result of each call of the `aggregate3` function in chunks bounded by `limit`
+    ///
+    /// Same as [`DynamicMulticallBuilder::aggregate3_stream`], but yields each result on its own,
+    /// paired with the index of its call in the builder, as soon as its chunk completes.
+    pub fn aggregate3_call_stream(
+        &self,
+        limit: impl Into<ChunkLimit>,
+    ) -> impl Stream<Item = Result<(usize, Result<Vec<DynSolValue>, Failure>), DynMulticallError>> + '_
+    {
+        self.aggregate3_stream(limit)
+            .map_ok(|chunk| stream::iter(chunk.range.zip(chunk.results).map(Ok)))
+            .try_flatten()
+    }
+
+    /// Executes the chunks with up to `max_concurrency` in flight, pinned to the same block and
+    /// checked for consistency if enabled.
+    async fn aggregate3_chunks(
+        &self,
+        chunks: &[(usize, &[DynCallItem])],
+        max_concurrency: usize,
+    ) -> Result<(Option<u64>, Vec<Result<Vec<DynSolValue>, Failure>>), DynMulticallError> {
+        let start = Instant::now();
+        let mut attempt = 0;
+
+        loop {
+            let (block, pinned_block) = self.chunk_block().await?;
+
+            let consistency_retries = match pinned_block {
+                Some(_) if chunks.len() > 1 => self.consistency_retries,
+                _ => None,
+            };
+
+            let progress = self.progress_tracker(chunks, start);
+
+            let futures = chunks.iter().map(|&(offset, chunk)| {
+                let progress = progress.as_deref();
+
+                async move {
+                    let result = self
+                        .aggregate3_chunk(chunk, offset, block, consistency_retries.is_some())
+                        .await;
+
+                    if let Some(progress) = progress {
+                        progress.chunk_completed(chunk.len());
+                    }
+
+                    result
+                }
+            });
+
+            let mut results = stream::iter(futures)
+                .buffer_unordered(max_concurrency)
+                .try_collect::<Vec<_>>()
+                .await?;
+
+            if let (Some(block), Some(max_retries)) = (pinned_block, consistency_retries) {
+                results.sort_unstable_by_key(|(offset, ..)| *offset);
+
+                let (parent_hashes, timestamps): (Vec<_>, Vec<_>) = results
+                    .iter()
+                    .filter_map(|(_, _, fingerprint)| *fingerprint)
+                    .unzip();
+
+                let consistent = parent_hashes.windows(2).all(|w| w[0] == w[1])
+                    && timestamps.windows(2).all(|w| w[0] == w[1]);
+
+                if !consistent {
+                    if attempt < max_retries {
+                        warn!(
+                            block,
+                            attempt, "chunks executed against different blocks, retrying"
+                        );
+                        attempt += 1;
+                        continue;
+                    }
+
+                    return Err(DynMulticallError::Consistency {
+                        block,
+                        parent_hashes,
+                        timestamps,
+                    });
+                }
+            }
+
+            let results = results
+                .into_iter()
+                .map(|(offset, results, _)| (offset, results))
+                .collect();
+
+            return Ok((pinned_block, reassemble_chunks(self.calls.len(), results)?));
+        }
+    }
+
+    /// Returns a tracker reporting the completion of the given chunks to the progress callback, if
+    /// set.
+    fn progress_tracker(
+        &self,
+        chunks: &[(usize, &[DynCallItem])],
+        start: Instant,
+    ) -> Option<Arc<ProgressTracker>> {
+        self.progress.clone().map(|callback| {
+            Arc::new(ProgressTracker {
+                callback,
+                start,
+                chunks_total: chunks.len(),
+                calls_total: chunks.iter().map(|(_, chunk)| chunk.len()).sum(),
+                chunks_completed: AtomicUsize::new(0),
+                calls_completed: AtomicUsize::new(0),
+            })
+        })
+    }
+
+    /// Executes a chunk at the given block, returning the index of its first call with its
+    /// results.
+    ///
+    /// When `fingerprint` is set, the chunk is prefixed with calls returning the parent hash and
+    /// timestamp of the block it executes against, which are returned alongside the results.
+    async fn aggregate3_chunk(
+        &self,
+        chunk: &[DynCallItem],
+        offset: usize,
+        block: Option<BlockId>,
+        fingerprint: bool,
+    ) -> Result<
+        (
+            usize,
+            Vec<Result<Vec<DynSolValue>, Failure>>,
+            Option<(B256, u64)>,
+        ),
+        DynMulticallError,
+    > {
+        trace!(
+            offset,
+            chunk_len = chunk.len(),
+            "Executing aggregate3 chunk"
+        );
+
+        if !fingerprint {
+            let results = self
+                .aggregate3_calls(chunk, offset, self.input_kind, block, self.retry)
+                .await?;
+
+            return Ok((offset, results, None));
+        }
+
+        let prefix = [
+            multicall_function(
+                "function getLastBlockHash() external view returns (bytes32 blockHash)",
+            ),
+            multicall_function(
+                "function getCurrentBlockTimestamp() external view returns (uint256 timestamp)",
+            ),
+        ]
+        .map(|function| DynCallItem::new(self.address, Vec::new(), function, false));
+
+        let calls = prefix
+            .into_iter()
+            .chain(chunk.iter().cloned())
+            .collect::<Vec<_>>();
+
+        let mut results = self
+            .execute_aggregate3(&calls, self.input_kind, block, self.retry)
+            .await?;
+
+        if results.len() != calls.len() {
+            return Err(MulticallError::NoReturnData.into());
+        }
+
+        let chunk_results = results.split_off(2);
+
+        let parent_hash = getLastBlockHashCall::abi_decode_returns(&results[0].returnData)
+            .map_err(MulticallError::DecodeError)?;
+        let timestamp = getCurrentBlockTimestampCall::abi_decode_returns(&results[1].returnData)
+            .map_err(MulticallError::DecodeError)?;
+
+        let results = decode_results(chunk, chunk_results, offset, self.strict_decode)?;
+
+        Ok((
+            offset,
+            results,
+            Some((parent_hash, timestamp.saturating_to())),
+        ))
+    }
+
+    /// Returns the block to execute chunks at, along with the block number if it was pinned by
+    /// fetching the current block number.
+    async fn chunk_block(&self) -> Result<(Option<BlockId>, Option<u64>)> {
+        if self.block.is_some() || !self.pin_blocks || self.calls.is_empty() {
+            return Ok((self.block, None));
+        }
+
+        let number = self
+            .send_with_policy(self.retry, || self.provider.get_block_number())
+            .await
+            .map_err(MulticallError::TransportError)?;
+
+        trace!(number, "Pinning chunks to block");
+
+        Ok((Some(BlockId::number(number)), Some(number)))
+    }
+
+    /// Returns a [`DynMulticallError::ValueInAggregate3`] with the indices of the calls that have
+    /// a value if values aren't allowed in `aggregate3`.
+    fn check_aggregate3_values(&self) -> Result<(), DynMulticallError> {
+        if self.allow_value_in_aggregate3 {
+            return Ok(());
+        }
+
+        check_no_values(&self.calls)
+    }
+
+    /// Calls the `aggregate3` function with the given calls.
+    ///
+    /// `offset` is the index of the first call in the builder, used for the index of a [`Failure`].
+    async fn aggregate3_calls(
+        &self,
+        calls: &[DynCallItem],
+        offset: usize,
+        input_kind: TransactionInputKind,
+        block: Option<BlockId>,
+        retry: Option<(usize, Duration)>,
+    ) -> Result<Vec<Result<Vec<DynSolValue>, Failure>>, DynMulticallError> {
+        if calls.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let results = self
+            .execute_aggregate3(calls, input_kind, block, retry)
+            .await?;
+
+        decode_results(calls, results, offset, self.strict_decode)
+    }
+
+    /// Encodes the given calls and calls the `aggregate3` function, returning the undecoded
+    /// results.
+    async fn execute_aggregate3(
+        &self,
+        calls: &[DynCallItem],
+        input_kind: TransactionInputKind,
+        block: Option<BlockId>,
+        retry: Option<(usize, Duration)>,
+    ) -> Result<Vec<MulticallResult>> {
+        let call = aggregate3Call {
+            calls: encode_calls3(calls)?,
+        };
+
+        trace!("aggregate3Call: {call:?}",);
+
+        let results = self
+            .build_and_call_with_input_kind(
+                &call,
+                None,
+                input_kind,
+                block,
+                self.call_state_override(),
+                retry,
+            )
+            .await?;
+
+        trace!("aggregate3Call results: {results:?}");
+
+        Ok(results)
+    }
+
+    /// Call the `aggregate3Value` function
+    ///
+    /// Similar to [`DynamicMulticallBuilder::aggregate3`], but forwards the `value` set on each
+    /// [`DynCallItem`] to its target.
+    ///
+    /// `aggregate3Value` only succeeds when `msg.value` is _strictly_ equal to the sum of the values
+    /// of all calls. The sum is computed and set on the transaction request by the builder.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error without making a request if the sum of the call values overflows [`U256`],
+    /// or a [`DynMulticallError::ZeroTarget`] like [`DynamicMulticallBuilder::aggregate3`].
+    pub async fn aggregate3_value(
+        &self,
+    ) -> Result<Vec<Result<Vec<DynSolValue>, Failure>>, DynMulticallError> {
+        self.with_deadline(async {
+            check_targets(&self.calls)?;
+
+            let calls = Self::encode_calls3_value(&self.calls)?;
+            let total_value = self.total_value()?;
+
+            let call = aggregate3ValueCall { calls };
+
+            trace!(%total_value, "aggregate3ValueCall: {call:?}");
+
+            let results = self.build_and_call(call, Some(total_value)).await?;
+
+            trace!("aggregate3ValueCall results: {results:?}");
+
+            decode_results(&self.calls, results, 0, self.strict_decode)
+        })
+        .await
+    }
+
+    /// Call the `tryAggregate` function
+    ///
+    /// Allows for calls to fail by setting `require_success` to false. This is also available on
+    /// Multicall2 deployments.
+    ///
+    /// The `allow_failure` flag of each [`DynCallItem`] is ignored in favour of `require_success`,
+    /// which applies to the whole batch. If `require_success` is true and any call fails, the
+    /// multicall reverts and the revert data is returned as a [`MulticallError::CallFailed`].
+    pub async fn try_aggregate(
+        &self,
+        require_success: bool,
+    ) -> Result<Vec<Result<Vec<DynSolValue>, Failure>>, DynMulticallError> {
+        self.with_deadline(async {
+            let calls = self.encode_calls()?;
+
+            let call = tryAggregateCall {
+                requireSuccess: require_success,
+                calls,
+            };
+
+            trace!("tryAggregateCall: {call:?}");
+
+            let results = self
+                .build_and_call(call, None)
+                .await
+                .map_err(revert_data_to_call_failed)?;
+
+            trace!("tryAggregateCall results: {results:?}");
+
+            decode_results(&self.calls, results, 0, self.strict_decode)
+        })
+        .await
+    }
+
+    /// Call the `blockAndAggregate` function
+    ///
+    /// Unlike [`DynamicMulticallBuilder::aggregate3`], the `allow_failure` flag of each
+    /// [`DynCallItem`] is ignored: `blockAndAggregate` is equivalent to `tryBlockAndAggregate` with
+    /// `requireSuccess` set to true, so the whole multicall reverts if any call fails. The revert data
+    /// is returned as a [`MulticallError::CallFailed`].
+    ///
+    /// ## Returns
+    ///
+    /// - The block number the multicall was executed at.
+    /// - The block hash the multicall was executed at. This is `blockhash(block.number)`, which
+    ///   most clients report as zero for the block being executed.
+    /// - The decoded return values for each call.
+    pub async fn block_and_aggregate(
+        &self,
+    ) -> Result<(u64, B256, Vec<Result<Vec<DynSolValue>, Failure>>), DynMulticallError> {
+        self.with_deadline(async {
+            let calls = self.encode_calls()?;
+
+            let call = blockAndAggregateCall { calls };
+
+            trace!("blockAndAggregateCall: {call:?}");
+
+            let blockAndAggregateReturn {
+                blockNumber,
+                blockHash,
+                returnData,
+            } = self
+                .build_and_call(call, None)
+                .await
+                .map_err(revert_data_to_call_failed)?;
+
+            trace!(%blockNumber, %blockHash, "blockAndAggregateCall results: {returnData:?}");
+
+            Ok((
+                block_number_to_u64(blockNumber)?,
+                blockHash,
+                decode_results(&self.calls, returnData, 0, self.strict_decode)?,
+            ))
+        })
+        .await
+    }
+
+    /// Call the `tryBlockAndAggregate` function
+    ///
+    /// Allows for calls to fail by setting `require_success` to false. As with
+    /// [`DynamicMulticallBuilder::try_aggregate`], the `allow_failure` flag of each [`DynCallItem`]
+    /// is ignored in favour of `require_success`.
+    ///
+    /// This is also available on Multicall2 deployments, which can be targeted with
+    /// [`DynamicMulticallBuilder::with_address`].
+    ///
+    /// ## Returns
+    ///
+    /// - The block number the multicall was executed at.
+    /// - The block hash the multicall was executed at, see
+    ///   [`DynamicMulticallBuilder::block_and_aggregate`].
+    /// - The decoded return values for each call, with failures carrying the index of the call.
+    pub async fn try_block_and_aggregate(
+        &self,
+        require_success: bool,
+    ) -> Result<(u64, B256, Vec<Result<Vec<DynSolValue>, Failure>>), DynMulticallError> {
+        self.with_deadline(async {
+            let calls = self.encode_calls()?;
+
+            let call = tryBlockAndAggregateCall {
+                requireSuccess: require_success,
+                calls,
+            };
+
+            trace!("tryBlockAndAggregateCall: {call:?}");
+
+            let tryBlockAndAggregateReturn {
+                blockNumber,
+                blockHash,
+                returnData,
+            } = self
+                .build_and_call(call, None)
+                .await
+                .map_err(revert_data_to_call_failed)?;
+
+            trace!(%blockNumber, %blockHash, "tryBlockAndAggregateCall results: {returnData:?}");
+
+            Ok((
+                block_number_to_u64(blockNumber)?,
+                blockHash,
+                decode_results(&self.calls, returnData, 0, self.strict_decode)?,
+            ))
+        })
+        .await
+    }
+
+    /// Get the eth balance of an address from the multicall contract
+    ///
+    /// Unlike [`DynamicMulticallBuilder::add_get_eth_balance`], this makes a standalone call
+    /// without the calls in the builder. Respects the configured address and block.
+    pub async fn get_eth_balance(&self, addr: Address) -> Result<U256, DynMulticallError> {
+        self.with_deadline(async {
+            Ok(self
+                .build_and_call(getEthBalanceCall { addr }, None)
+                .await?)
+        })
+        .await
+    }
+
+    /// Get the block number from the multicall contract
+    ///
+    /// Respects the configured address and block.
+    pub async fn get_block_number(&self) -> Result<u64, DynMulticallError> {
+        self.with_deadline(async {
+            let block_number = self.build_and_call(getBlockNumberCall {}, None).await?;
+
+            block_number_to_u64(block_number)
+        })
+        .await
+    }
+
+    /// Get the timestamp of the current block from the multicall contract
+    ///
+    /// Respects the configured address and block.
+    pub async fn get_current_block_timestamp(&self) -> Result<U256, DynMulticallError> {
+        self.with_deadline(async {
+            Ok(self
+                .build_and_call(getCurrentBlockTimestampCall {}, None)
+                .await?)
+        })
+        .await
+    }
+
+    /// Estimate the gas used by calling the `aggregate3` function with the calls in the builder
+    ///
+    /// Estimates the same request as [`DynamicMulticallBuilder::aggregate3`], with the configured
+    /// value, call gas limit and fee fields, at the configured block and with the configured state
+    /// overrides, including the Multicall3 code override if
+    /// [`DynamicMulticallBuilder::with_injected_multicall`] is set. Not all nodes support state
+    /// overrides for `eth_estimateGas`, in which case the node's error is returned.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`DynMulticallError::ValueInAggregate3`] if any call has a value and values
+    /// aren't allowed in `aggregate3`.
+    pub async fn estimate_gas(&self) -> Result<u64, DynMulticallError> {
+        self.with_deadline(async {
+            self.check_aggregate3_values()?;
+
+            let call = aggregate3Call {
+                calls: encode_calls3(&self.calls)?,
+            };
+
+            let mut tx = self.build_tx(&call, None);
+            self.apply_call_fields(&mut tx);
+
+            let mut estimate = self.provider.root().estimate_gas(tx);
+
+            if let Some(block) = self.block {
+                estimate = estimate.block(block);
+            }
+
+            if let Some(overrides) = self.call_state_override() {
+                estimate = estimate.overrides(overrides);
+            }
+
+            Ok(estimate.await.map_err(MulticallError::TransportError)?)
+        })
+        .await
+    }
+
+    /// Create an access list for calling the `aggregate3` function with the calls in the builder
+    ///
+    /// Respects the configured block and state overrides, including the Multicall3 code override if
+    /// [`DynamicMulticallBuilder::with_injected_multicall`] is set, though not all nodes support
+    /// state overrides for `eth_createAccessList`. The list can be set with
+    /// [`DynamicMulticallBuilder::with_access_list`] to pre-warm storage slots when sending the
+    /// batch.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the node reports that the call fails.
+    pub async fn create_access_list(&self) -> Result<AccessListWithGasUsed, DynMulticallError> {
+        self.with_deadline(async {
+            self.check_aggregate3_values()?;
+
+            let call = aggregate3Call {
+                calls: encode_calls3(&self.calls)?,
+            };
+
+            let mut tx = self.build_tx(&call, None);
+            self.apply_call_fields(&mut tx);
+
+            let mut params = EthCallParams::<N>::new(tx);
+
+            if let Some(block) = self.block {
+                params = params.with_block(block);
+            }
+
+            if let Some(overrides) = self.call_state_override() {
+                params = params.with_overrides(overrides);
+            }
+
+            let result: AccessListResult = self
+                .provider
+                .client()
+                .request("eth_createAccessList", params)
+                .await
+                .map_err(MulticallError::TransportError)?;
+
+            trace!(?result, "eth_createAccessList result");
+
+            Ok(result.ensure_ok().map_err(|err| {
+                MulticallError::TransportError(TransportErrorKind::custom_str(&err))
+            })?)
+        })
+        .await
+    }
+
+    /// Build the multicall transaction request without sending it
+    ///
+    /// Encodes an `aggregate3Value` call with the sum of the call values if any call has a value,
+    /// and an `aggregate3` call otherwise. The request can be filled, signed and sent through a
+    /// custom pipeline, e.g. added to a bundle.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`DynMulticallError::ValueOverflow`] if the sum of the call values overflows.
+    pub fn build_transaction(&self) -> Result<N::TransactionRequest, DynMulticallError> {
+        let total_value = self.total_value()?;
+
+        let tx = if total_value.is_zero() {
+            let call = aggregate3Call {
+                calls: encode_calls3(&self.calls)?,
+            };
+
+            trace!("Building aggregate3Call: {call:?}");
+
+            self.build_tx(&call, None)
+        } else {
+            let call = aggregate3ValueCall {
+                calls: Self::encode_calls3_value(&self.calls)?,
+            };
+
+            trace!(%total_value, "Building aggregate3ValueCall: {call:?}");
+
+            self.build_tx(&call, Some(total_value))
+        };
+
+        Ok(tx)
+    }
+
+    /// Build the calldata of the `aggregate3` call made by [`DynamicMulticallBuilder::aggregate3`]
+    ///
+    /// The response can be decoded with [`decode_aggregate3_response`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`DynMulticallError::ValueInAggregate3`] if any call has a value and values
+    /// aren't allowed in `aggregate3`.
+    pub fn build_calldata(&self) -> Result<Bytes, DynMulticallError> {
+        self.check_aggregate3_values()?;
+
+        Ok(encode_aggregate3(&self.calls)?)
+    }
+
+    /// Get the size in bytes of the calldata of the `aggregate3` call
+    ///
+    /// Exact size of [`DynamicMulticallBuilder::build_calldata`], including the selector. The
+    /// calldata of each call is still encoded, only the final `aggregate3` encoding is skipped,
+    /// so this costs about as much as building the calldata. Useful to flush a batch before
+    /// hitting the request size limit of an RPC.
+    pub fn calldata_size(&self) -> Result<usize, DynMulticallError> {
+        let call = aggregate3Call {
+            calls: encode_calls3(&self.calls)?,
+        };
+
+        Ok(aggregate3Call::SELECTOR.len() + call.abi_encoded_size())
+    }
+
+    /// Get the target and calldata of each call in the builder without making a request
+    ///
+    /// The calldata is the call's params encoded with its function selector, as sent to the
+    /// target in the batch, e.g. to paste into a calldata decoder when debugging encoding issues.
+    pub fn encoded_calls(&self) -> Result<Vec<(Address, Bytes)>, DynMulticallError> {
+        self.calls
+            .iter()
+            .map(|c| Ok((c.target, c.encode_input()?)))
+            .collect()
+    }
+
+    /// Build the `eth_call` request made by [`DynamicMulticallBuilder::aggregate3`] without
+    /// sending it
+    ///
+    /// The request uses the configured input kind, value, sender and call fields. The block and
+    /// state overrides are not part of the request and have to be passed along separately.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`DynMulticallError::ValueInAggregate3`] if any call has a value and values
+    /// aren't allowed in `aggregate3`.
+    pub fn build_request(&self) -> Result<N::TransactionRequest, DynMulticallError> {
+        self.check_aggregate3_values()?;
+
+        let call = aggregate3Call {
+            calls: encode_calls3(&self.calls)?,
+        };
+
+        let mut tx = self.build_tx(&call, None);
+        self.apply_call_fields(&mut tx);
+
+        Ok(tx)
+    }
+
+    /// Send the calls as an on-chain transaction and wait for its receipt
+    ///
+    /// The transaction is built with [`DynamicMulticallBuilder::build_transaction`] and sent
+    /// through the provider, which is responsible for filling and signing it.
+    pub async fn send(&self) -> Result<N::ReceiptResponse, DynMulticallError> {
+        let tx = self.build_transaction()?;
+
+        let pending_tx = self
+            .provider
+            .send_transaction(tx)
+            .await
+            .map_err(MulticallError::TransportError)?;
+
+        trace!(tx_hash = %pending_tx.tx_hash(), "Sent multicall transaction");
+
+        let receipt = pending_tx.get_receipt().await.map_err(|err| match err {
+            PendingTransactionError::TransportError(err) => MulticallError::TransportError(err),
+            err => MulticallError::TransportError(TransportErrorKind::custom(err)),
+        })?;
+
+        Ok(receipt)
+    }
+
+    /// Encodes the calls into Multicall3 [`Call3Value`] structs.
+    fn encode_calls3_value(calls: &[DynCallItem]) -> Result<Vec<Call3Value>> {
+        calls
+            .iter()
+            .map(|c| {
+                Ok(Call3Value {
+                    target: c.target,
+                    allowFailure: c.allow_failure,
+                    value: c.value,
+                    callData: c.encode_input()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Encodes the calls into Multicall [`Call`] structs, dropping `allow_failure` and `value`.
+    fn encode_calls(&self) -> Result<Vec<Call>> {
+        self.calls
+            .iter()
+            .map(|c| {
+                Ok(Call {
+                    target: c.target,
+                    callData: c.encode_input()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Re-executes each call as an individual `eth_call` after the whole batch reverted, returning
+    /// a [`DynMulticallError::BatchReverted`] with the outcome of each call, see
+    /// [`DynamicMulticallBuilder::with_revert_fallback`].
+    ///
+    /// Calls are re-executed with the input kind and retry policy of the batch. Returns `err` as is
+    /// if it isn't a revert, or if a call can't be re-executed.
+    async fn diagnose_revert(
+        &self,
+        err: DynMulticallError,
+        input_kind: TransactionInputKind,
+        retry: Option<(usize, Duration)>,
+    ) -> DynMulticallError {
+        let revert_data = match &err {
+            DynMulticallError::Multicall(MulticallError::TransportError(transport_err)) => {
+                execution_revert_data(transport_err)
+            }
+            _ => None,
+        };
+
+        let Some(revert_data) = revert_data else {
+            return err;
+        };
+
+        debug!(%revert_data, "aggregate3 reverted, re-executing calls individually");
+
+        let outcomes = stream::iter(self.calls.iter().enumerate().map(|(idx, c)| async move {
+            // Calls are made by the multicall contract in the batch.
+            let mut tx = N::TransactionRequest::default()
+                .with_from(self.address)
+                .with_to(c.target)
+                .with_input_kind(c.encode_input()?, input_kind);
+            self.apply_call_fields(&mut tx);
+
+            let res = self
+                .eth_call(tx, self.block, self.call_state_override(), retry)
+                .await;
+
+            let revert_data = match res {
+                Ok(_) => None,
+                Err(err) => match execution_revert_data(&err) {
+                    Some(revert_data) => Some(revert_data),
+                    None => return Err(MulticallError::TransportError(err)),
+                },
+            };
+
+            Ok(CallOutcome {
+                idx,
+                target: c.target,
+                function: c.decoder.signature(),
+                revert_data,
+            })
+        }))
+        .buffered(REVERT_FALLBACK_CONCURRENCY)
+        .try_collect::<Vec<_>>()
+        .await;
+
+        match outcomes {
+            Ok(outcomes) => DynMulticallError::BatchReverted {
+                revert_data,
+                outcomes,
+            },
+            Err(diagnose_err) => {
+                warn!(%diagnose_err, "failed to re-execute calls individually");
+                err
+            }
+        }
+    }
+
+    /// Re-executes the failed calls that are allowed to fail one by one, see
+    /// [`DynamicMulticallBuilder::retry_failures_individually`], with the input kind and retry
+    /// policy of the batch.
+    async fn retry_failures(
+        &self,
+        results: &mut [Result<Vec<DynSolValue>, Failure>],
+        input_kind: TransactionInputKind,
+        retry: Option<(usize, Duration)>,
+    ) {
+        for (idx, result) in results.iter_mut().enumerate() {
+            let call = &self.calls[idx..=idx];
+            if !call[0].allow_failure {
+                continue;
+            }
+
+            for attempt in 0..self.failure_retries {
+                if result.is_ok() {
+                    break;
+                }
+
+                trace!(idx, attempt, "Retrying failed call");
+
+                match self
+                    .aggregate3_calls(call, idx, input_kind, self.block, retry)
+                    .await
+                {
+                    Ok(mut retried) if retried.len() == 1 => *result = retried.remove(0),
+                    Ok(_) => break,
+                    Err(err) => {
+                        warn!(idx, attempt, %err, "retrying failed call errored");
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Runs `execution` within the deadline set with [`DynamicMulticallBuilder::with_timeout`], if
+    /// any.
+    async fn with_deadline<T>(
+        &self,
+        execution: impl Future<Output = Result<T, DynMulticallError>>,
+    ) -> Result<T, DynMulticallError> {
+        match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, execution)
+                .await
+                .map_err(|_| DynMulticallError::Timeout(timeout))?,
+            None => execution.await,
+        }
+    }
+
+    /// Returns the delay before the given retry attempt.
+    fn retry_delay(&self, base_delay: Duration, attempt: usize) -> Duration {
+        let factor = u32::try_from(attempt)
+            .ok()
+            .and_then(|attempt| 1u32.checked_shl(attempt))
+            .unwrap_or(u32::MAX);
+        let delay = base_delay.saturating_mul(factor);
+
+        let Some(seed) = self.retry_jitter_seed else {
+            return delay;
+        };
+
+        delay / 2 + (delay / 2).mul_f64(jitter(seed, attempt))
+    }
+
+    /// Returns the delay before retrying a rate limited `eth_call` for the given attempt, or `None`
+    /// if the error isn't a rate limit or the retries set with
+    /// [`DynamicMulticallBuilder::with_rate_limit_retry`] are exhausted.
+    fn rate_limit_delay(&self, err: &TransportError, attempt: usize) -> Option<Duration> {
+        let max_retries = self.rate_limit_retries?;
+
+        if attempt >= max_retries || !is_rate_limited(err) {
+            return None;
+        }
+
+        let delay = backoff_hint(err).unwrap_or_else(|| {
+            self.retry_delay(RATE_LIMIT_BASE_DELAY, attempt)
+                .min(RATE_LIMIT_MAX_DELAY)
+        });
+
+        Some(delay)
+    }
+
+    /// Helper fn to build a tx calling the multicall contract
+    fn build_tx<M: SolCall>(&self, call_type: &M, value: Option<U256>) -> N::TransactionRequest {
+        self.build_tx_with_input_kind(call_type, value, self.input_kind)
+    }
+
+    /// Helper fn to build a tx calling the multicall contract with the given input kind
+    fn build_tx_with_input_kind<M: SolCall>(
+        &self,
+        call_type: &M,
+        value: Option<U256>,
+        input_kind: TransactionInputKind,
+    ) -> N::TransactionRequest {
+        let call = call_type.abi_encode();
+
+        let mut tx = N::TransactionRequest::default()
+            .with_to(self.address)
+            .with_input_kind(Bytes::from_iter(call), input_kind);
+
+        let value = match (self.value, value) {
+            (Some(value), Some(total_value)) => {
+                if value != total_value {
+                    warn!(
+                        %value,
+                        %total_value,
+                        "multicall value doesn't match the sum of the call values"
+                    );
+                }
+                Some(value)
+            }
+            (value, total_value) => value.or(total_value),
+        };
+
+        if let Some(value) = value {
+            tx.set_value(value);
+        }
+
+        self.apply_tx_fields(&mut tx);
+
+        tx
+    }
+
+    /// Sets the transaction fields configured on the builder on `tx`.
+    fn apply_tx_fields(&self, tx: &mut N::TransactionRequest) {
+        if let Some(from) = self.from {
+            tx.set_from(from);
+        }
+        if let Some(access_list) = self.access_list.clone() {
+            tx.set_access_list(access_list);
+        }
+    }
+
+    /// Sets the fields configured on the builder that only apply to an `eth_call` on `tx`.
+    fn apply_call_fields(&self, tx: &mut N::TransactionRequest) {
+        if let Some(gas_limit) = self.call_gas_limit {
+            tx.set_gas_limit(gas_limit);
+        }
+        if let Some(gas_price) = self.gas_price {
+            tx.set_gas_price(gas_price);
+        }
+        if let Some(max_fee_per_gas) = self.max_fee_per_gas {
+            tx.set_max_fee_per_gas(max_fee_per_gas);
+        }
+        if let Some(max_priority_fee_per_gas) = self.max_priority_fee_per_gas {
+            tx.set_max_priority_fee_per_gas(max_priority_fee_per_gas);
+        }
+    }
+
+    /// Maps the transport error of an `eth_call` into a [`MulticallError`].
+    ///
+    /// Out of gas errors mention the configured call gas limit, if set.
+    fn call_error(&self, err: TransportError) -> MulticallError {
+        match self.call_gas_limit {
+            Some(gas_limit) if is_out_of_gas(&err) => {
+                MulticallError::TransportError(TransportErrorKind::custom_str(&format!(
+                    "{err} (call gas limit set to {gas_limit})"
+                )))
+            }
+            _ => MulticallError::TransportError(err),
+        }
+    }
+
+    /// Returns the state overrides for an `eth_call`, including the Multicall3 code override if
+    /// [`DynamicMulticallBuilder::with_injected_multicall`] is set.
+    fn call_state_override(&self) -> Option<StateOverride> {
+        self.inject_multicall_code(self.state_override.clone())
+    }
+
+    /// Adds the Multicall3 code override to the given state overrides if enabled, see
+    /// [`DynamicMulticallBuilder::with_injected_multicall`].
+    fn inject_multicall_code(&self, mut overrides: Option<StateOverride>) -> Option<StateOverride> {
+        if self.inject_multicall {
+            let account = overrides
+                .get_or_insert_with(Default::default)
+                .entry(self.address)
+                .or_default();
+
+            // Code set by the user for the multicall address takes precedence.
+            if account.code.is_none() {
+                account.code = Some(multicall3_runtime_code());
+            }
+        }
+
+        overrides
+    }
+
+    /// Helper fn to build a tx and call the multicall contract
+    async fn build_and_call<M: SolCall>(
+        &self,
+        call_type: M,
+        value: Option<U256>,
+    ) -> Result<M::Return> {
+        self.build_and_call_with_input_kind(
+            &call_type,
+            value,
+            self.input_kind,
+            self.block,
+            self.call_state_override(),
+            self.retry,
+        )
+        .await
+    }
+
+    /// Helper fn to build a tx with the given input kind and call the multicall contract at the
+    /// given block with the given state overrides, retrying with the given policy
+    async fn build_and_call_with_input_kind<M: SolCall>(
+        &self,
+        call_type: &M,
+        value: Option<U256>,
+        input_kind: TransactionInputKind,
+        block: Option<BlockId>,
+        state_override: Option<StateOverride>,
+        retry: Option<(usize, Duration)>,
+    ) -> Result<M::Return> {
+        let mut tx = self.build_tx_with_input_kind(call_type, value, input_kind);
+        self.apply_call_fields(&mut tx);
+
+        let res = self
+            .eth_call(tx, block, state_override, retry)
+            .await
+            .map_err(|err| self.call_error(err))?;
+
+        M::abi_decode_returns(&res).map_err(MulticallError::DecodeError)
+    }
+
+    /// Makes an `eth_call` at the given block with the given state overrides, retrying with the
+    /// given policy, see [`DynamicMulticallBuilder::send_with_policy`].
+    async fn eth_call(
+        &self,
+        tx: N::TransactionRequest,
+        block: Option<BlockId>,
+        state_override: Option<StateOverride>,
+        retry: Option<(usize, Duration)>,
+    ) -> TransportResult<Bytes> {
+        self.send_with_policy(retry, || {
+            let mut eth_call = self.provider.root().call(tx.clone());
+
+            if let Some(block) = block {
+                eth_call = eth_call.block(block);
+            }
+
+            if let Some(overrides) = state_override.clone() {
+                eth_call = eth_call.overrides(overrides);
+            }
+
+            async move { eth_call.await }
+        })
+        .await
+    }
+
+    /// Sends the request made by `request`, bounding each attempt with
+    /// [`DynamicMulticallBuilder::with_call_timeout`], and retrying rate limits with
+    /// [`DynamicMulticallBuilder::with_rate_limit_retry`] and transient errors with the given
+    /// policy, see [`DynamicMulticallBuilder::with_retry`].
+    async fn send_with_policy<T, Fut>(
+        &self,
+        retry: Option<(usize, Duration)>,
+        mut request: impl FnMut() -> Fut,
+    ) -> TransportResult<T>
+    where
+        Fut: Future<Output = TransportResult<T>>,
+    {
+        let mut attempt = 0;
+        let mut rate_limit_attempt = 0;
+
+        let res = loop {
+            let res = match self.call_timeout {
+                Some(timeout) => tokio::time::timeout(timeout, request())
+                    .await
+                    .unwrap_or_else(|_| Err(TransportErrorKind::custom(CallTimeout(timeout)))),
+                None => request().await,
+            };
+
+            let err = match res {
+                Ok(res) => break res,
+                Err(err) => err,
+            };
+
+            if let Some(delay) = self.rate_limit_delay(&err, rate_limit_attempt) {
+                warn!(
+                    attempt = rate_limit_attempt,
+                    ?delay,
+                    %err,
+                    "request rate limited, retrying"
+                );
+
+                tokio::time::sleep(delay).await;
+                rate_limit_attempt += 1;
+                continue;
+            }
+
+            match retry {
+                Some((max_retries, base_delay))
+                    if attempt < max_retries && is_transient_transport(&err) =>
+                {
+                    let delay = self.retry_delay(base_delay, attempt);
+                    warn!(attempt, ?delay, %err, "request failed, retrying");
+
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                _ => return Err(err),
+            }
+        };
+
+        if attempt + rate_limit_attempt > 0 {
+            debug!(
+                attempts = attempt + rate_limit_attempt + 1,
+                "request succeeded after retrying"
+            );
+        }
+
+        Ok(res)
+    }
+
+    /// Returns a builder with empty calls.
+    ///
+    /// Retains the provider and all previously set configuration, only the queued calls are
+    /// removed.
+    pub fn clear(self) -> Self {
+        Self {
+            calls: Vec::new(),
+            ..self
+        }
+    }
+
+    /// Returns a builder using the given provider.
+    ///
+    /// Retains the calls and all previously set settings, e.g. to run the same calls against
+    /// another node.
+    pub fn with_provider<P2: Provider<N>>(self, provider: P2) -> DynamicMulticallBuilder<P2, N> {
+        DynamicMulticallBuilder {
+            calls: self.calls,
+            provider,
+            block: self.block,
+            state_override: self.state_override,
+            address: self.address,
+            input_kind: self.input_kind,
+            allow_value_in_aggregate3: self.allow_value_in_aggregate3,
+            inject_multicall: self.inject_multicall,
+            from: self.from,
+            call_gas_limit: self.call_gas_limit,
+            gas_price: self.gas_price,
+            max_fee_per_gas: self.max_fee_per_gas,
+            max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+            access_list: self.access_list,
+            value: self.value,
+            retry_jitter_seed: self.retry_jitter_seed,
+            strict_decode: self.strict_decode,
+            pin_blocks: self.pin_blocks,
+            consistency_retries: self.consistency_retries,
+            progress: self.progress,
+            retry: self.retry,
+            rate_limit_retries: self.rate_limit_retries,
+            timeout: self.timeout,
+            call_timeout: self.call_timeout,
+            failure_retries: self.failure_retries,
+            revert_fallback: self.revert_fallback,
+            errors: self.errors,
+            _pd: Default::default(),
+        }
+    }
+
+    /// Get the calls in the builder
+    pub fn calls(&self) -> &[DynCallItem] {
+        &self.calls
+    }
+
+    /// Get the number of calls in the builder
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    /// Check if the builder is empty
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    /// Get the number of calls to each target
+    ///
+    /// Useful to log or sanity check a batch before executing it, e.g. to spot calls added twice.
+    pub fn calls_by_target(&self) -> HashMap<Address, usize> {
+        let mut counts = HashMap::new();
+
+        for call in &self.calls {
+            *counts.entry(call.target).or_default() += 1;
+        }
+
+        counts
+    }
+
+    /// Get the sum of the values of all calls
+    ///
+    /// This is the `msg.value` required by [`DynamicMulticallBuilder::aggregate3_value`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`DynMulticallError::ValueOverflow`] if the sum overflows [`U256`].
+    pub fn total_value(&self) -> Result<U256, DynMulticallError> {
+        self.calls
+            .iter()
+            .try_fold(U256::ZERO, |acc, c| acc.checked_add(c.value))
+            .ok_or(DynMulticallError::ValueOverflow)
+    }
+
+    /// Set the input kind for this builder
+    pub const fn with_input_kind(mut self, input_kind: TransactionInputKind) -> Self {
+        self.input_kind = input_kind;
+        self
+    }
+
+    /// Set the calldata in both the `input` and `data` fields of the requests made by the builder
+    ///
+    /// Shorthand for [`TransactionInputKind::Both`], for nodes that only read one of the fields.
+    pub const fn with_input_and_data(self) -> Self {
+        self.with_input_kind(TransactionInputKind::Both)
+    }
+
+    /// Get the input kind for this builder
+    pub const fn input_kind(&self) -> TransactionInputKind {
+        self.input_kind
+    }
+
+    /// Set the address of the multicall contract
+    ///
+    /// Default is [`MULTICALL3_ADDRESS`].
+    pub const fn with_address(mut self, address: Address) -> Self {
+        self.address = address;
+        self
+    }
+
+    /// Set the sender of the multicall transaction
+    ///
+    /// Targets still see the multicall contract as `msg.sender`, so this mainly affects the
+    /// multicall call itself, e.g. funding the `msg.value` of
+    /// [`DynamicMulticallBuilder::aggregate3_value`] or simulating with a funded caller. Nodes
+    /// default to the zero address when unset.
+    pub const fn with_from(mut self, from: Address) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    /// Get the sender of the multicall transaction, if set.
+    pub const fn from(&self) -> Option<Address> {
+        self.from
+    }
+
+    /// Set the gas limit of the `eth_call` made by the builder
+    ///
+    /// Defaults to the gas cap of the node when unset. Out of gas errors mention the configured
+    /// limit.
+    pub const fn with_call_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.call_gas_limit = Some(gas_limit);
+        self
+    }
+
+    /// Get the gas limit of the `eth_call` made by the builder, if set.
+    pub const fn call_gas_limit(&self) -> Option<u64> {
+        self.call_gas_limit
+    }
+
+    /// Set the gas price of the `eth_call` made by the builder
+    ///
+    /// Omitted from the request when unset, as some RPCs reject unexpected fee fields on calls.
+    pub const fn with_gas_price(mut self, gas_price: u128) -> Self {
+        self.gas_price = Some(gas_price);
+        self
+    }
+
+    /// Set the EIP-1559 max fee per gas of the `eth_call` made by the builder
+    ///
+    /// Omitted from the request when unset.
+    pub const fn with_max_fee_per_gas(mut self, max_fee_per_gas: u128) -> Self {
+        self.max_fee_per_gas = Some(max_fee_per_gas);
+        self
+    }
+
+    /// Set the EIP-1559 max priority fee per gas of the `eth_call` made by the builder
+    ///
+    /// Omitted from the request when unset.
+    pub const fn with_max_priority_fee_per_gas(mut self, max_priority_fee_per_gas: u128) -> Self {
+        self.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
+        self
+    }
+
+    /// Retry the `eth_call`s made by the builder on transient transport errors
+    ///
+    /// Each `eth_call` is retried up to `max_retries` times, doubling the delay between attempts
+    /// starting from `base_delay`, like [`DynamicMulticallBuilder::aggregate3_with_retry`]. Only
+    /// transport level errors such as dropped connections and HTTP 5xx, and retryable error
+    /// responses such as rate limits, are retried. Execution reverts, other error responses and
+    /// decoding errors aren't. Chunks are separate `eth_call`s, so each chunk is retried
+    /// independently. Disabled by default.
+    ///
+    /// Covers every `eth_call` of the `aggregate3` methods, the batch of
+    /// [`DynamicMulticallBuilder::execute_as_individual_calls`], the deployless call, the
+    /// re-executions of [`DynamicMulticallBuilder::with_revert_fallback`] and the block number
+    /// request pinning chunks. [`DynamicMulticallBuilder::verify_deployment`],
+    /// [`DynamicMulticallBuilder::estimate_gas`], [`DynamicMulticallBuilder::create_access_list`]
+    /// and [`DynamicMulticallBuilder::send`] aren't retried.
+    pub const fn with_retry(mut self, max_retries: usize, base_delay: Duration) -> Self {
+        self.retry = Some((max_retries, base_delay));
+        self
+    }
+
+    /// Retry the `eth_call`s made by the builder when rate limited by the RPC
+    ///
+    /// Each `eth_call` is retried up to `max_retries` times on HTTP 429 or rate limit error
+    /// responses, independently of [`DynamicMulticallBuilder::with_retry`], e.g. `usize::MAX` to
+    /// wait out rate limits while still failing fast on other errors. Only Infura's backoff hint,
+    /// `rate.backoff_seconds` in the error response body, is honored; `Retry-After` headers aren't
+    /// exposed by the transport and are ignored. Otherwise the delay doubles from 1 second up to
+    /// 30 seconds. Applies to the same requests as [`DynamicMulticallBuilder::with_retry`].
+    /// Disabled by default.
+    pub const fn with_rate_limit_retry(mut self, max_retries: usize) -> Self {
+        self.rate_limit_retries = Some(max_retries);
+        self
+    }
+
+    /// Bound the whole execution of each method making requests, including all chunks and retries
+    ///
+    /// Returns a [`DynMulticallError::Timeout`] once `timeout` has elapsed, dropping the requests
+    /// still in flight. Applies to the `aggregate`, `aggregate3` and `tryAggregate` families,
+    /// [`DynamicMulticallBuilder::execute_as_individual_calls`],
+    /// [`DynamicMulticallBuilder::verify_deployment`], [`DynamicMulticallBuilder::estimate_gas`],
+    /// [`DynamicMulticallBuilder::create_access_list`] and the `get_*` helpers.
+    ///
+    /// [`DynamicMulticallBuilder::resolve_address`] and [`DynamicMulticallBuilder::send`], which
+    /// waits for the receipt, aren't bounded. The streamed execution of
+    /// [`DynamicMulticallBuilder::aggregate3_stream`] is paced by its consumer and isn't bounded
+    /// either. Disabled by default.
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Bound each request made by the builder
+    ///
+    /// A request still pending after `timeout` fails with a [`MulticallError::TransportError`]
+    /// wrapping a [`CallTimeout`], which is retried by [`DynamicMulticallBuilder::with_retry`],
+    /// unlike the overall deadline set with [`DynamicMulticallBuilder::with_timeout`]. Chunks are
+    /// separate `eth_call`s, so each chunk is bounded independently. Each attempt of the requests
+    /// retried by [`DynamicMulticallBuilder::with_retry`] is bounded. Disabled by default.
+    pub const fn with_call_timeout(mut self, timeout: Duration) -> Self {
+        self.call_timeout = Some(timeout);
+        self
+    }
+
+    /// Randomize the delays of [`DynamicMulticallBuilder::aggregate3_with_retry`],
+    /// [`DynamicMulticallBuilder::with_retry`] and
+    /// [`DynamicMulticallBuilder::with_rate_limit_retry`]
+    ///
+    /// Each delay is picked between half and all of its exponential backoff value, so that many
+    /// clients failing at once don't retry in lockstep. The jitter is drawn from a random seed
+    /// picked once, see [`DynamicMulticallBuilder::with_retry_jitter_seed`] for reproducible
+    /// delays. Disabled by default, which keeps the delays exact.
+    pub fn with_retry_jitter(self) -> Self {
+        self.with_retry_jitter_seed(RandomState::new().hash_one(0))
+    }
+
+    /// Randomize the retry delays like [`DynamicMulticallBuilder::with_retry_jitter`], drawing the
+    /// jitter from the given seed
+    ///
+    /// The same seed always gives the same delay for a given attempt, e.g. to test retries.
+    pub const fn with_retry_jitter_seed(mut self, seed: u64) -> Self {
+        self.retry_jitter_seed = Some(seed);
+        self
+    }
+
+    /// Set the `msg.value` of the multicall transaction
+    ///
+    /// Takes precedence over the sum of the call values computed by
+    /// [`DynamicMulticallBuilder::aggregate3_value`] and [`DynamicMulticallBuilder::send`], with a
+    /// warning logged if they don't match. Note that `aggregate3Value` reverts unless the value is
+    /// equal to the sum of the call values.
+    pub const fn with_value(mut self, value: U256) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    /// Get the `msg.value` of the multicall transaction, if set.
+    pub const fn value(&self) -> Option<U256> {
+        self.value
+    }
+
+    /// Set the EIP-2930 access list of the multicall transaction
+    ///
+    /// See [`DynamicMulticallBuilder::create_access_list`] to generate one.
+    pub fn with_access_list(mut self, access_list: AccessList) -> Self {
+        self.access_list = Some(access_list);
+        self
+    }
+
+    /// Get the access list of the multicall transaction, if set.
+    pub const fn access_list(&self) -> Option<&AccessList> {
+        self.access_list.as_ref()
+    }
+
+    /// Inject the Multicall3 runtime bytecode at the multicall address via a state override.
+    ///
+    /// This allows batching calls on chains without a Multicall3 deployment, as long as the RPC
+    /// supports state overrides. The code override is merged with any other state overrides, and
+    /// applies to `eth_call` based methods and [`DynamicMulticallBuilder::estimate_gas`], not
+    /// [`DynamicMulticallBuilder::send`].
+    pub const fn with_injected_multicall(mut self) -> Self {
+        self.inject_multicall = true;
+        self
+    }
+
+    /// Get the address of the multicall contract
+    pub const fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Sets the block to be used for the call.
+    ///
+    /// Accepts anything convertible into a [`BlockId`], e.g. a block number or block hash.
+    pub fn with_block(mut self, block: impl Into<BlockId>) -> Self {
+        self.block = Some(block.into());
+        self
+    }
+
+    /// Sets the block to be used for the call by its number.
+    pub const fn at_block_number(mut self, number: u64) -> Self {
+        self.block = Some(BlockId::number(number));
+        self
+    }
+
+    /// Sets the block to be used for the call by its hash.
+    ///
+    /// The call fails if the node doesn't know a block with this hash, e.g. after a reorg.
+    pub const fn at_block_hash(mut self, hash: B256) -> Self {
+        self.block = Some(BlockId::hash(hash));
+        self
+    }
+
+    /// Sets the block to be used for the call to the `latest` tag.
+    pub const fn at_latest(mut self) -> Self {
+        self.block = Some(BlockId::latest());
+        self
+    }
+
+    /// Sets the block to be used for the call to the `pending` tag.
+    ///
+    /// The tag is passed through as is, some nodes treat it the same as `latest`.
+    pub const fn at_pending(mut self) -> Self {
+        self.block = Some(BlockId::pending());
+        self
+    }
+
+    /// Sets the block to be used for the call to the `safe` tag.
+    pub const fn at_safe(mut self) -> Self {
+        self.block = Some(BlockId::safe());
+        self
+    }
+
+    /// Sets the block to be used for the call to the `finalized` tag.
+    pub const fn at_finalized(mut self) -> Self {
+        self.block = Some(BlockId::finalized());
+        self
+    }
+
+    /// Get the block to be used for the call, if set.
+    ///
+    /// The block is retained by [`DynamicMulticallBuilder::clear`].
+    pub const fn block(&self) -> Option<BlockId> {
+        self.block
+    }
+
+    /// Don't pin the chunks of [`DynamicMulticallBuilder::aggregate3_chunked`] to the current block
+    ///
+    /// Each chunk then executes against the latest block when no block is set, which may differ
+    /// between chunks. Useful for pruned nodes rejecting calls at a block number.
+    pub const fn without_block_pinning(mut self) -> Self {
+        self.pin_blocks = false;
+        self
+    }
+
+    /// Verify that the chunks of [`DynamicMulticallBuilder::aggregate3_chunked`] execute against the
+    /// same block
+    ///
+    /// A reorg between chunks can replace the pinned block, mixing pre- and post-reorg state. When
+    /// enabled, each chunk is prefixed with Multicall3's `getLastBlockHash` and
+    /// `getCurrentBlockTimestamp`, as a block can't read its own hash, and the results of all
+    /// chunks are compared. On a mismatch, the whole run is retried from a freshly pinned block up
+    /// to `max_retries` times, before returning a [`DynMulticallError::Consistency`].
+    ///
+    /// Only applies to runs with several chunks pinned to the current block, see
+    /// [`DynamicMulticallBuilder::without_block_pinning`].
+    ///
+    /// The parent hash and timestamp don't identify the block itself: a reorg replacing the pinned
+    /// block with a sibling built on the same parent in the same slot isn't detected. Pin the
+    /// block by hash with [`DynamicMulticallBuilder::with_block`] and [`BlockId::hash`] instead
+    /// when that matters, as the node then rejects calls at a block that has been reorged out.
+    pub const fn with_consistency_check(mut self, max_retries: usize) -> Self {
+        self.consistency_retries = Some(max_retries);
+        self
+    }
+
+    /// Set a callback reporting the progress of chunked executions
+    ///
+    /// The callback is invoked after each chunk of [`DynamicMulticallBuilder::aggregate3_chunked`],
+    /// [`DynamicMulticallBuilder::aggregate3_concurrent`] and
+    /// [`DynamicMulticallBuilder::aggregate3_stream`] completes, whether it succeeded or not, and
+    /// after each successful chunk of [`DynamicMulticallBuilder::aggregate3_adaptive`]. A panic in
+    /// the callback is caught and logged rather than aborting the execution.
+    pub fn with_progress(mut self, callback: impl Fn(Progress) + Send + Sync + 'static) -> Self {
+        self.progress = Some(ProgressCallback(Arc::new(callback)));
+        self
+    }
+
+    /// Check that the decoded outputs of each successful call account for its whole return data.
+    ///
+    /// Catches calls to the wrong contract or with the wrong ABI, whose return data would
+    /// otherwise be silently decoded into the wrong values, e.g. a function returning two words
+    /// decoded as a single `uint256`. A mismatch fails the whole batch with a decode error
+    /// including the target and function signature. Defaults to lenient decoding.
+    pub const fn with_strict_decode(mut self) -> Self {
+        self.strict_decode = true;
+        self
+    }
+
+    /// Register custom error definitions to decode the reverts of failed calls with
+    ///
+    /// Used by [`DynamicMulticallBuilder::revert_reason`] and
+    /// [`DynamicMulticallBuilder::simulate`], e.g. with the errors of a contract's ABI from
+    /// `abi.errors().cloned()`. Adds to the errors registered before.
+    pub fn with_errors(mut self, errors: impl IntoIterator<Item = AbiError>) -> Self {
+        self.errors.extend(errors);
+        self
+    }
+
+    /// Get the custom error definitions registered with [`DynamicMulticallBuilder::with_errors`]
+    pub fn errors(&self) -> &[AbiError] {
+        &self.errors
+    }
+
+    /// Decode the revert reason of a failed call, including the custom errors registered with
+    /// [`DynamicMulticallBuilder::with_errors`]
+    ///
+    /// See [`RevertReason::decode_with_errors`].
+    pub fn revert_reason(&self, failure: &Failure) -> RevertReason {
+        RevertReason::decode_with_errors(&failure.return_data, &self.errors)
+    }
+
+    /// Re-execute each call individually when the whole batch reverts in
+    /// [`DynamicMulticallBuilder::aggregate3`]
+    ///
+    /// A call that isn't allowed to fail reverting makes the whole batch revert, without telling
+    /// which call did. With the fallback enabled, each call is then re-executed as an individual
+    /// `eth_call` from the multicall address, at the same block and with the same state
+    /// overrides, and a [`DynMulticallError::BatchReverted`] is returned with the outcome of each
+    /// call. Up to 16 calls are re-executed at once, and reverts without data are reported with
+    /// empty revert data. Unless a block is set, the calls may execute against a later block than
+    /// the batch. Defaults to returning the revert as is.
+    pub const fn with_revert_fallback(mut self) -> Self {
+        self.revert_fallback = true;
+        self
+    }
+
+    /// Retry the failed calls that are allowed to fail after [`DynamicMulticallBuilder::aggregate3`]
+    ///
+    /// Each such call is re-executed on its own, up to `attempts` times until it succeeds, and its
+    /// result is replaced with the latest outcome, keeping the call order and [`Failure::idx`].
+    /// Useful for calls failing because a node briefly served bad state, without retrying the
+    /// whole batch. Retries run at the configured block, so unless one is set they may see a later
+    /// block than the batch. A retry erroring, e.g. with a transport error, keeps the failure.
+    /// Defaults to no retries.
+    pub const fn retry_failures_individually(mut self, attempts: usize) -> Self {
+        self.failure_retries = attempts;
+        self
+    }
+
+    /// Set whether calls with a non-zero value are allowed in
+    /// [`DynamicMulticallBuilder::aggregate3`].
+    ///
+    /// When enabled, the values are silently ignored. Defaults to `false`.
+    pub const fn allow_value_in_aggregate3(mut self, allow: bool) -> Self {
+        self.allow_value_in_aggregate3 = allow;
+        self
+    }
+
+    /// Set the state overrides for the call.
+    ///
+    /// This replaces any existing overrides, see [`DynamicMulticallBuilder::merge_state_override`]
+    /// to merge them instead.
+    pub fn with_state_override(mut self, overrides: StateOverride) -> Self {
+        self.state_override = Some(overrides);
+        self
+    }
+
+    /// Get the state overrides for the call, if set.
+    pub const fn state_override(&self) -> Option<&StateOverride> {
+        self.state_override.as_ref()
+    }
+
+    /// Override the balance of an account for the call.
+    ///
+    /// Merges with any other overrides for the account.
+    pub fn override_balance(mut self, address: Address, balance: U256) -> Self {
+        self.account_override_mut(address).balance = Some(balance);
+        self
+    }
+
+    /// Override the code of an account for the call.
+    ///
+    /// Merges with any other overrides for the account.
+    pub fn override_code(mut self, address: Address, code: impl Into<Bytes>) -> Self {
+        self.account_override_mut(address).code = Some(code.into());
+        self
+    }
+
+    /// Override a single storage slot of an account for the call.
+    ///
+    /// Merges with any other overrides for the account.
+    pub fn override_storage(mut self, address: Address, slot: B256, value: B256) -> Self {
+        self.account_override_mut(address)
+            .state_diff
+            .get_or_insert_with(Default::default)
+            .insert(slot, value);
+        self
+    }
+
+    /// Override individual storage slots of an account for the call.
+    ///
+    /// Merges with any other overrides for the account.
+    pub fn override_state_diff(
+        mut self,
+        address: Address,
+        state_diff: impl IntoIterator<Item = (B256, B256)>,
+    ) -> Self {
+        self.account_override_mut(address)
+            .state_diff
+            .get_or_insert_with(Default::default)
+            .extend(state_diff);
+        self
+    }
+
+    /// Get the override for an account, creating the state overrides if not set.
+    fn account_override_mut(&mut self, address: Address) -> &mut AccountOverride {
+        self.state_override
+            .get_or_insert_with(Default::default)
+            .entry(address)
+            .or_default()
+    }
+
+    /// Get a mutable reference to the state overrides, allowing them to be built incrementally.
+    pub fn state_override_mut(&mut self) -> &mut Option<StateOverride> {
+        &mut self.state_override
+    }
+
+    /// Merge the given state overrides on top of the existing ones.
+    ///
+    /// Overrides for the same account are merged field by field, with the given values winning on
+    /// conflict. Returns a [`DynMulticallError::OverrideConflict`], leaving the existing overrides
+    /// unchanged, if an account would end up with both a `state` and a `state_diff` override.
+    pub fn merge_state_override(&mut self, other: StateOverride) -> Result<(), DynMulticallError> {
+        let mut merged = self.state_override.clone().unwrap_or_default();
+
+        for (address, account) in other {
+            merge_account_override(address, merged.entry(address).or_default(), account)?;
+        }
+
+        self.state_override = Some(merged);
+        Ok(())
+    }
+}
+
+impl<P, N> Default for DynamicMulticallBuilder<P, N>
+where
+    P: Provider<N> + Default,
+    N: Network,
+{
+    /// Same as [`DynamicMulticallBuilder::new`] with the default provider.
+    fn default() -> Self {
+        Self::new(P::default())
+    }
+}
+
+impl<'a, P, N> IntoIterator for &'a DynamicMulticallBuilder<P, N>
+where
+    P: Provider<N>,
+    N: Network,
+{
+    type Item = &'a DynCallItem;
+    type IntoIter = std::slice::Iter<'a, DynCallItem>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.calls.iter()
+    }
+}
+
+impl<P, N> Index<usize> for DynamicMulticallBuilder<P, N>
+where
+    P: Provider<N>,
+    N: Network,
+{
+    type Output = DynCallItem;
+
+    fn index(&self, idx: usize) -> &Self::Output {
+        &self.calls[idx]
+    }
+}
+
+/// Merges `other` into the `base` override of an account, with the values of `other` winning.
+fn merge_account_override(
+    address: Address,
+    base: &mut AccountOverride,
+    other: AccountOverride,
+) -> Result<(), DynMulticallError> {
+    if other.balance.is_some() {
+        base.balance = other.balance;
+    }
+    if other.nonce.is_some() {
+        base.nonce = other.nonce;
+    }
+    if other.code.is_some() {
+        base.code = other.code;
+    }
+    if other.move_precompile_to.is_some() {
+        base.move_precompile_to = other.move_precompile_to;
+    }
+    if other.state.is_some() {
+        base.state = other.state;
+    }
+    if let Some(state_diff) = other.state_diff {
+        base.state_diff
+            .get_or_insert_with(Default::default)
+            .extend(state_diff);
+    }
+
+    if base.state.is_some() && base.state_diff.is_some() {
+        return Err(DynMulticallError::OverrideConflict { address });
+    }
+
+    Ok(())
+}
+
+/// Decode the revert reason from the return data of a failed call.
+///
+/// Decodes `Error(string)` and `Panic(uint256)` reverts, as well as raw string reverts. Custom
+/// errors are returned as their raw selector, e.g. `"custom error 0x12345678"`. Returns `None` if
+/// the data is empty or can't be decoded.
+///
+/// This only gives a string in the format of
+/// [`alloy::sol_types::decode_revert_reason`], see [`RevertReason`] for the structured reason.
+pub fn decode_revert_reason(data: &[u8]) -> Option<String> {
+    if data.is_empty() {
+        return None;
+    }
+
+    alloy::sol_types::decode_revert_reason(data).or_else(|| {
+        data.get(..4)
+            .map(|selector| format!("custom error {}", hex::encode_prefixed(selector)))
+    })
+}
+
+/// Key the decoded output values of a function by the names of its outputs.
+///
+/// Unnamed outputs are keyed by their position, e.g. `"0"` for the first output.
+pub fn named_outputs(
+    function: &Function,
+    values: Vec<DynSolValue>,
+) -> BTreeMap<String, DynSolValue> {
+    function
+        .outputs
+        .iter()
+        .zip(values)
+        .enumerate()
+        .map(|(idx, (output, value))| {
+            let name = match output.name.is_empty() {
+                true => idx.to_string(),
+                false => output.name.clone(),
+            };
+            (name, value)
+        })
+        .collect()
+}
+
+/// Split the results of a multicall into the successful calls and the failures.
+///
+/// Each success is paired with the index of its call, matching [`Failure::idx`] on the failure
+/// side. Both sides keep the call order.
+pub fn partition_results(
+    results: Vec<Result<Vec<DynSolValue>, Failure>>,
+) -> (Vec<(usize, Vec<DynSolValue>)>, Vec<Failure>) {
+    let mut successes = Vec::new();
+    let mut failures = Vec::new();
+
+    for (idx, result) in results.into_iter().enumerate() {
+        match result {
+            Ok(values) => successes.push((idx, values)),
+            Err(failure) => failures.push(failure),
+        }
+    }
+
+    (successes, failures)
+}
+
+/// Fan the results of deduplicated calls back out to the original calls.
+///
+/// `mapping` is the index of the remaining call for each original call, as returned by
+/// [`DynamicMulticallBuilder::dedup`]. The index of each [`Failure`] is set to the position of the
+/// original call.
+///
+/// ## Panics
+///
+/// Panics if an index in `mapping` is out of bounds for `results`.
+pub fn fan_out_results(
+    results: &[Result<Vec<DynSolValue>, Failure>],
+    mapping: &[usize],
+) -> Vec<Result<Vec<DynSolValue>, Failure>> {
+    mapping
+        .iter()
+        .enumerate()
+        .map(|(orig_idx, &idx)| {
+            results[idx].clone().map_err(|failure| Failure {
+                idx: orig_idx,
+                ..failure
+            })
+        })
+        .collect()
+}
+
+/// Encode the calldata of an `aggregate3` call with the given calls.
+///
+/// The `value` of each call is dropped, see [`DynamicMulticallBuilder::aggregate3`].
+pub fn encode_aggregate3(calls: &[DynCallItem]) -> Result<Bytes> {
+    let call = aggregate3Call {
+        calls: encode_calls3(calls)?,
+    };
+
+    Ok(call.abi_encode().into())
+}
+
+/// Decode the return data of an `aggregate3` call made with the given calls.
+///
+/// Decodes the results exactly like [`DynamicMulticallBuilder::aggregate3`], without a provider,
+/// e.g. for responses captured from traces.
+pub fn decode_aggregate3_response(
+    calls: &[DynCallItem],
+    raw: &[u8],
+) -> Result<Vec<Result<Vec<DynSolValue>, Failure>>, DynMulticallError> {
+    let results = aggregate3Call::abi_decode_returns(raw).map_err(MulticallError::DecodeError)?;
+
+    decode_results(calls, results, 0, false)
+}
+
+/// Decode the result of a call into the return type of a [`SolCall`].
+///
+/// The decoded values are re-encoded and decoded as `C::Return`, allowing strongly-typed outputs
+/// for the calls whose types are known at compile time.
+///
+/// ## Errors
+///
+/// Returns a [`MulticallError::CallFailed`] with the return data if the call failed, or a
+/// [`MulticallError::DecodeError`] if the values don't match the return type of `C`.
+pub fn decode_as<C: SolCall>(result: &Result<Vec<DynSolValue>, Failure>) -> Result<C::Return> {
+    let values = result
+        .as_ref()
+        .map_err(|failure| MulticallError::CallFailed(failure.return_data.clone()))?;
+
+    let encoded = DynSolValue::Tuple(values.clone()).abi_encode_params();
+
+    C::abi_decode_returns(&encoded).map_err(MulticallError::DecodeError)
+}
+
+/// Length of the init code preceding the runtime code in the Multicall3 creation bytecode.
+const MULTICALL3_INIT_CODE_LEN: usize = 32;
+
+/// Returns the Multicall3 runtime bytecode, as deployed at [`MULTICALL3_ADDRESS`].
+fn multicall3_runtime_code() -> Bytes {
+    IMulticall3::BYTECODE.slice(MULTICALL3_INIT_CODE_LEN..)
+}
+
+/// Length of the deployless helper code in [`deployless_init_code`].
+const DEPLOYLESS_HELPER_LEN: u16 = 59;
+
+/// Returns the init code of a deployless multicall.
+///
+/// The init code is laid out as `helper || multicall3 creation code || calldata`. The helper
+/// deploys Multicall3 with `CREATE`, calls it with the calldata and returns its return data, or
+/// reverts with it if the call failed.
+fn deployless_init_code(calldata: &[u8]) -> Bytes {
+    let creation_code_len = IMulticall3::BYTECODE.len() as u16;
+    let [code_hi, code_lo] = creation_code_len.to_be_bytes();
+    let [helper_hi, helper_lo] = DEPLOYLESS_HELPER_LEN.to_be_bytes();
+    let [data_hi, data_lo] = (DEPLOYLESS_HELPER_LEN + creation_code_len).to_be_bytes();
+
+    #[rustfmt::skip]
+    let helper: [u8; DEPLOYLESS_HELPER_LEN as usize] = [
+        // codecopy(0, helper_len, creation_code_len)
+        0x61, code_hi, code_lo,     // PUSH2 creation_code_len
+        0x61, helper_hi, helper_lo, // PUSH2 helper_len
+        0x60, 0x00,                 // PUSH1 0
+        0x39,                       // CODECOPY
+        // address = create(0, 0, creation_code_len)
+        0x61, code_hi, code_lo,     // PUSH2 creation_code_len
+        0x60, 0x00,                 // PUSH1 0
+        0x60, 0x00,                 // PUSH1 0
+        0xf0,                       // CREATE
+        // calldata_len = codesize - data_offset
+        0x61, data_hi, data_lo,     // PUSH2 data_offset
+        0x38,                       // CODESIZE
+        0x03,                       // SUB
+        // codecopy(0, data_offset, calldata_len)
+        0x80,                       // DUP1
+        0x61, data_hi, data_lo,     // PUSH2 data_offset
+        0x60, 0x00,                 // PUSH1 0
+        0x39,                       // CODECOPY
+        // success = call(gas, address, 0, 0, calldata_len, 0, 0)
+        0x60, 0x00,                 // PUSH1 0
+        0x60, 0x00,                 // PUSH1 0
+        0x82,                       // DUP3
+        0x60, 0x00,                 // PUSH1 0
+        0x60, 0x00,                 // PUSH1 0
+        0x86,                       // DUP7
+        0x5a,                       // GAS
+        0xf1,                       // CALL
+        // returndatacopy(0, 0, returndatasize)
+        0x3d,                       // RETURNDATASIZE
+        0x60, 0x00,                 // PUSH1 0
+        0x60, 0x00,                 // PUSH1 0
+        0x3e,                       // RETURNDATACOPY
+        // if success, return(0, returndatasize), else revert(0, returndatasize)
+        0x60, 0x36,                 // PUSH1 return_dest
+        0x57,                       // JUMPI
+        0x3d,                       // RETURNDATASIZE
+        0x60, 0x00,                 // PUSH1 0
+        0xfd,                       // REVERT
+        0x5b,                       // JUMPDEST (return_dest)
+        0x3d,                       // RETURNDATASIZE
+        0x60, 0x00,                 // PUSH1 0
+        0xf3,                       // RETURN
+    ];
+
+    [&helper[..], &IMulticall3::BYTECODE[..], calldata]
+        .concat()
+        .into()
+}
+
+/// Returns the known Multicall3 address for a chain, falling back to [`MULTICALL3_ADDRESS`].
+fn multicall_address_for_chain(chain_id: u64) -> Address {
+    known_multicall_address(chain_id).unwrap_or_else(|| {
+        warn!(
+            chain_id,
+            "unknown multicall address for chain, using the canonical address"
+        );
+        MULTICALL3_ADDRESS
+    })
+}
+
+/// Encodes the calls into Multicall3 [`Call3`] structs, dropping `value`.
+fn encode_calls3(calls: &[DynCallItem]) -> Result<Vec<Call3>> {
+    calls
+        .iter()
+        .map(|c| {
+            Ok(Call3 {
+                target: c.target,
+                callData: c.encode_input()?,
+                allowFailure: c.allow_failure,
+            })
+        })
+        .collect()
+}
+
+/// Decodes the
+/// [`Result`](alloy::providers::bindings::IMulticall3::Result) structs returned by the
+/// multicall contract using the decoder of each call.
+///
+/// `offset` is the index of the first call in the builder, used for the index of a [`Failure`].
+/// When `strict` is set, the decoded values must account for the whole return data, see
+/// [`DynamicMulticallBuilder::with_strict_decode`].
+fn decode_results(
+    calls: &[DynCallItem],
+    results: Vec<MulticallResult>,
+    offset: usize,
+    strict: bool,
+) -> Result<Vec<Result<Vec<DynSolValue>, Failure>>, DynMulticallError> {
+    if results.len() != calls.len() {
+        return Err(MulticallError::NoReturnData.into());
+    }
+
+    let mut decoded_results: Vec<Result<Vec<DynSolValue>, Failure>> =
+        Vec::with_capacity(results.len());
+
+    for (local_idx, (call, result)) in calls.iter().zip(results).enumerate() {
+        let idx = offset + local_idx;
+
+        trace!(
+            idx,
+            ?result,
+            target = %call.target,
+            function = %call.decoder.name,
+            "Attempting to decode result"
+        );
+
+        let decoded_call_result = match result.success {
+            true => {
+                // Calls to some contracts with a fallback will return success, but actually return no data.
+                // Functions without outputs (e.g. `deposit()`) legitimately return no data.
+                if result.returnData.is_empty() && !call.decoder.outputs.is_empty() {
+                    Err(Failure {
+                        idx,
+                        return_data: result.returnData,
+                    })
+                } else {
+                    let decoded =
+                        call.decoder
+                            .abi_decode_output(&result.returnData)
+                            .map_err(|source| DynMulticallError::Decode {
+                                idx,
+                                target: call.target,
+                                function: call.decoder.name.clone(),
+                                signature: call.decoder.full_signature(),
+                                return_data: result.returnData.clone(),
+                                source: Box::new(source),
+                            })?;
+
+                    if strict {
+                        check_decoded_len(idx, call, &decoded, &result.returnData)?;
+                    }
+
+                    Ok(decoded)
+                }
+            }
+            false => Err(Failure {
+                idx,
+                return_data: result.returnData,
+            }),
+        };
+
+        decoded_results.push(decoded_call_result);
+    }
+
+    Ok(decoded_results)
+}
+
+/// Returns an error if the decoded values of a call don't account for its whole return data.
+fn check_decoded_len(
+    idx: usize,
+    call: &DynCallItem,
+    decoded: &[DynSolValue],
+    return_data: &[u8],
+) -> Result<(), DynMulticallError> {
+    let decoded_len = call
+        .decoder
+        .abi_encode_output(decoded)
+        .map_err(|source| DynMulticallError::Decode {
+            idx,
+            target: call.target,
+            function: call.decoder.name.clone(),
+            signature: call.decoder.full_signature(),
+            return_data: Bytes::copy_from_slice(return_data),
+            source: Box::new(source),
+        })?
+        .len();
+
+    if decoded_len != return_data.len() {
+        return Err(DynMulticallError::OutputLengthMismatch {
+            idx,
+            target: call.target,
+            signature: call.decoder.full_signature(),
+            return_data_len: return_data.len(),
+            decoded_len,
+        });
+    }
+
+    Ok(())
+}
+
+/// Converts a block number returned by the multicall contract, returning a
+/// [`DynMulticallError::BlockNumberOverflow`] if it doesn't fit a `u64`.
+fn block_number_to_u64(block_number: U256) -> Result<u64, DynMulticallError> {
+    u64::try_from(block_number).map_err(|_| DynMulticallError::BlockNumberOverflow(block_number))
+}
+
+/// Returns a fraction in `[0, 1)` for the jitter of the given retry attempt, mixing the seed and
+/// attempt with SplitMix64.
+fn jitter(seed: u64, attempt: usize) -> f64 {
+    let mut z = seed.wrapping_add(
+        (attempt as u64)
+            .wrapping_add(1)
+            .wrapping_mul(0x9e37_79b9_7f4a_7c15),
+    );
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Returns true if the transport error may succeed on retry: a transport level error such as a
+/// dropped connection or a [`CallTimeout`], an HTTP 5xx or 429, or a retryable error response
+/// such as a rate limit.
+///
+/// Execution reverts, other error responses such as invalid params, and serialization errors
+/// aren't transient.
+fn is_transient_transport(err: &TransportError) -> bool {
+    match err {
+        RpcError::Transport(TransportErrorKind::HttpError(err)) => {
+            err.status >= 500 || err.is_rate_limit_err()
+        }
+        RpcError::Transport(TransportErrorKind::PubsubUnavailable) => false,
+        _ if CallTimeout::is_call_timeout(err) => true,
+        RpcError::Transport(_) => true,
+        RpcError::ErrorResp(payload) => {
+            let message = payload.message.to_lowercase();
+
+            payload.is_retry_err() && message != "header not found" && !message.contains("revert")
+        }
+        _ => false,
+    }
+}
+
+/// Initial delay before retrying a rate limited `eth_call`, doubled on each attempt.
+const RATE_LIMIT_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// Maximum delay before retrying a rate limited `eth_call`.
+const RATE_LIMIT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Returns true if the transport error is an HTTP 429 or a rate limit error response.
+fn is_rate_limited(err: &TransportError) -> bool {
+    match err {
+        RpcError::Transport(TransportErrorKind::HttpError(err)) => err.is_rate_limit_err(),
+        RpcError::Transport(err) => err.to_string().contains("429 Too Many Requests"),
+        // Infura's load balancer errors are retryable, but aren't rate limits.
+        RpcError::ErrorResp(payload) => {
+            payload.is_retry_err() && payload.message != "header not found"
+        }
+        _ => false,
+    }
+}
+
+/// Returns the backoff delay requested in a rate limit error response, if any.
+///
+/// Only Infura's `rate.backoff_seconds` in the error data is supported. `Retry-After` headers
+/// can't be read, as the HTTP transport error doesn't keep the response headers.
+fn backoff_hint(err: &TransportError) -> Option<Duration> {
+    let data = err
+        .as_error_resp()?
+        .try_data_as::<serde_json::Value>()?
+        .ok()?;
+    let backoff_seconds = &data["rate"]["backoff_seconds"];
+
+    backoff_seconds
+        .as_u64()
+        .map(Duration::from_secs)
+        .or_else(|| Duration::try_from_secs_f64(backoff_seconds.as_f64()?).ok())
+}
+
+/// Places the results of chunks completed in any order back at the index of their call.
+///
+/// Each chunk is given with the index of its first call. Returns a
+/// [`DynMulticallError::ChunkReassembly`] if a result falls out of range, or if any index is left
+/// without a result or gets several.
+fn reassemble_chunks<T>(
+    len: usize,
+    chunks: Vec<(usize, Vec<T>)>,
+) -> Result<Vec<T>, DynMulticallError> {
+    let mut slots = std::iter::repeat_with(|| None)
+        .take(len)
+        .collect::<Vec<_>>();
+
+    for (offset, results) in chunks {
+        for (idx, result) in (offset..).zip(results) {
+            let slot = slots
+                .get_mut(idx)
+                .ok_or(DynMulticallError::ChunkReassembly { idx })?;
+
+            if slot.replace(result).is_some() {
+                return Err(DynMulticallError::ChunkReassembly { idx });
+            }
+        }
+    }
+
+    slots
+        .into_iter()
+        .enumerate()
+        .map(|(idx, slot)| slot.ok_or(DynMulticallError::ChunkReassembly { idx }))
+        .collect()
+}
+
+/// Returns a [`DynMulticallError::ZeroTarget`] if a call that isn't allowed to fail targets the
+/// zero address.
+fn check_targets(calls: &[DynCallItem]) -> Result<(), DynMulticallError> {
+    match calls
+        .iter()
+        .position(|c| c.target.is_zero() && !c.allow_failure)
+    {
+        Some(idx) => Err(DynMulticallError::ZeroTarget { idx }),
+        None => Ok(()),
+    }
+}
+
+/// Size of the `aggregate3` calldata without any call: selector, array offset and array length.
+const AGGREGATE3_BASE_SIZE: usize = 4 + 32 + 32;
+
+/// Size added to the `aggregate3` calldata by a call with `calldata_len` bytes of calldata: the
+/// offset, target, allowFailure and calldata offset words, then the padded calldata bytes.
+const fn call3_encoded_size(calldata_len: usize) -> usize {
+    32 + 3 * 32 + 32 + calldata_len.div_ceil(32) * 32
+}
+
+/// Splits the calls into consecutive chunks bounded by `limit`, along with the index of the
+/// first call of each chunk.
+fn split_chunks(
+    calls: &[DynCallItem],
+    limit: ChunkLimit,
+) -> Result<Vec<(usize, &[DynCallItem])>, DynMulticallError> {
+    match limit {
+        ChunkLimit::Calls(0) | ChunkLimit::Bytes(0) => Err(DynMulticallError::EmptyChunkSize),
+        ChunkLimit::Calls(max_calls) => Ok(calls
+            .chunks(max_calls)
+            .enumerate()
+            .map(|(chunk_idx, chunk)| (chunk_idx * max_calls, chunk))
+            .collect()),
+        ChunkLimit::Bytes(max_bytes) => {
+            let mut chunks = Vec::new();
+            let mut start = 0;
+            let mut size = AGGREGATE3_BASE_SIZE;
+
+            for (idx, call) in calls.iter().enumerate() {
+                let call_size = call3_encoded_size(call.encode_input()?.len());
+
+                // A call exceeding the budget on its own still gets its own chunk.
+                if idx > start && size + call_size > max_bytes {
+                    chunks.push((start, &calls[start..idx]));
+                    start = idx;
+                    size = AGGREGATE3_BASE_SIZE;
+                }
+
+                size += call_size;
+            }
+
+            if start < calls.len() {
+                chunks.push((start, &calls[start..]));
+            }
+
+            Ok(chunks)
+        }
+    }
+}
+
+/// Parses the [`Function`] definition of one of the multicall contract's own functions.
+fn multicall_function(signature: &str) -> Function {
+    Function::parse(signature).expect("valid multicall function signature")
+}
+
+/// Maximum number of calls re-executed at once by
+/// [`DynamicMulticallBuilder::with_revert_fallback`].
+const REVERT_FALLBACK_CONCURRENCY: usize = 16;
+
+/// Returns the revert data of an execution revert error response, or empty data if the node
+/// didn't send any, e.g. geth's plain `execution reverted` error for a `require` without a message.
+fn execution_revert_data(err: &TransportError) -> Option<Bytes> {
+    let payload = err.as_error_resp()?;
+
+    payload.as_revert_data().or_else(|| {
+        payload
+            .message
+            .to_lowercase()
+            .contains("revert")
+            .then(Bytes::new)
+    })
+}
+
+/// Returns a [`DynMulticallError::ValueInAggregate3`] with the indices of the calls that have a
+/// value, if any.
+fn check_no_values(calls: &[DynCallItem]) -> Result<(), DynMulticallError> {
+    let indices = calls
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| !c.value.is_zero())
+        .map(|(idx, _)| idx)
+        .collect::<Vec<_>>();
+
+    if !indices.is_empty() {
+        warn!(
+            ?indices,
+            "aggregate3 batch contains calls with a value, use aggregate3_value instead"
+        );
+        return Err(DynMulticallError::ValueInAggregate3 { indices });
+    }
+
+    Ok(())
+}
+
+/// Returns true if the error looks like the call ran out of gas.
+fn is_out_of_gas(err: &TransportError) -> bool {
+    let msg = err.to_string().to_lowercase();
+
+    msg.contains("out of gas") || msg.contains("outofgas") || msg.contains("gas required exceeds")
+}
+
+/// Returns true if the error looks like the call exceeded the node's `eth_call` gas cap: an out
+/// of gas or gas limit error, or a revert without any data.
+fn is_gas_cap_error(err: &DynMulticallError) -> bool {
+    match err {
+        DynMulticallError::Multicall(MulticallError::TransportError(err)) => {
+            let msg = err.to_string().to_lowercase();
+
+            is_out_of_gas(err)
+                || msg.contains("gas limit")
+                || msg.contains("gas cap")
+                || execution_revert_data(err).is_some_and(|data| data.is_empty())
+        }
+        DynMulticallError::Multicall(MulticallError::CallFailed(data)) => data.is_empty(),
+        _ => false,
+    }
+}
+
+/// Maps a transport error carrying revert data into a [`MulticallError::CallFailed`].
+fn revert_data_to_call_failed(err: MulticallError) -> MulticallError {
+    match err {
+        MulticallError::TransportError(transport_err) => {
+            match transport_err
+                .as_error_resp()
+                .and_then(|resp| resp.as_revert_data())
+            {
+                Some(revert_data) => MulticallError::CallFailed(revert_data),
+                None => MulticallError::TransportError(transport_err),
+            }
+        }
+        err => err,
+    }
+}
+
+/// The limit of a chunk of calls in [`DynamicMulticallBuilder::aggregate3_chunked`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChunkLimit {
+    /// A maximum number of calls per chunk.
+    Calls(usize),
+    /// A maximum size in bytes of the `aggregate3` calldata of a chunk.
+    ///
+    /// Calls are packed greedily, and a call exceeding the budget on its own is sent in its own
+    /// chunk.
+    Bytes(usize),
+}
+
+impl From<usize> for ChunkLimit {
+    fn from(max_calls: usize) -> Self {
+        Self::Calls(max_calls)
+    }
+}
+
+/// The progress of a chunked execution, see [`DynamicMulticallBuilder::with_progress`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Progress {
+    /// The number of chunks completed so far.
+    pub chunks_completed: usize,
+    /// The total number of chunks, estimated from the current chunk size in
+    /// [`DynamicMulticallBuilder::aggregate3_adaptive`].
+    pub chunks_total: usize,
+    /// The number of calls in the chunks completed so far.
+    pub calls_completed: usize,
+    /// The total number of calls.
+    pub calls_total: usize,
+    /// The time elapsed since the start of the execution.
+    pub elapsed: Duration,
+}
+
+/// A progress callback set with [`DynamicMulticallBuilder::with_progress`].
+#[derive(Clone)]
+struct ProgressCallback(Arc<dyn Fn(Progress) + Send + Sync>);
+
+impl Debug for ProgressCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+/// Tracks the chunks completed during a chunked execution.
+struct ProgressTracker {
+    callback: ProgressCallback,
+    start: Instant,
+    chunks_total: usize,
+    calls_total: usize,
+    chunks_completed: AtomicUsize,
+    calls_completed: AtomicUsize,
+}
+
+impl ProgressTracker {
+    /// Records a completed chunk of `calls` calls and reports the progress to the callback.
+    fn chunk_completed(&self, calls: usize) {
+        let progress = Progress {
+            chunks_completed: self.chunks_completed.fetch_add(1, Ordering::Relaxed) + 1,
+            chunks_total: self.chunks_total,
+            calls_completed: self.calls_completed.fetch_add(calls, Ordering::Relaxed) + calls,
+            calls_total: self.calls_total,
+            elapsed: self.start.elapsed(),
+        };
+
+        report_progress(&self.callback, progress);
+    }
+}
+
+/// Reports the progress to the callback, catching and logging a panic in the callback.
+fn report_progress(callback: &ProgressCallback, progress: Progress) {
+    if panic::catch_unwind(AssertUnwindSafe(|| (callback.0)(progress))).is_err() {
+        warn!(?progress, "progress callback panicked");
+    }
+}
+
+/// The stats of an adaptive execution, see [`DynamicMulticallBuilder::aggregate3_adaptive`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AdaptiveStats {
+    /// The block number the chunks were pinned to, if any.
+    pub block: Option<u64>,
+    /// The chunk size of the last successful chunk.
+    pub final_chunk_size: usize,
+    /// The number of times a chunk was split after exceeding the gas cap.
+    pub splits: usize,
+    /// The number of `aggregate3` requests sent, including the failed ones.
+    pub requests: usize,
+}
+
+/// The results of a chunk of calls, see [`DynamicMulticallBuilder::aggregate3_stream`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChunkResults {
+    /// The indices of the calls of the chunk in the builder.
+    pub range: Range<usize>,
+    /// The block number the chunk was pinned to, if any.
+    pub block: Option<u64>,
+    /// The decoded return values for each call of the chunk.
+    pub results: Vec<Result<Vec<DynSolValue>, Failure>>,
+}
+
+/// The outcome of a call re-executed on its own after its batch reverted, see
+/// [`DynamicMulticallBuilder::with_revert_fallback`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallOutcome {
+    /// The index of the call in the builder.
+    pub idx: usize,
+    /// The target of the call.
+    pub target: Address,
+    /// The signature of the function called, e.g. `balanceOf(address)`.
+    pub function: String,
+    /// The revert data if the call reverted, or `None` if it succeeded.
+    pub revert_data: Option<Bytes>,
+}
+
+/// A failed call with the context of its call, see [`DynamicMulticallBuilder::aggregate3_detailed`]
+///
+/// Displays as e.g. `balanceOf(0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2) at index 3 reverted:
+/// ERC20: insufficient balance`, and converts losslessly into a [`Failure`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct DynFailure {
+    /// The index of the call in the builder.
+    pub idx: usize,
+    /// The return data of the call.
+    pub return_data: Bytes,
+    /// The target of the call.
+    pub target: Address,
+    /// The name of the function called, e.g. `balanceOf`.
+    pub function: String,
+    /// The selector of the function called.
+    pub selector: FixedBytes<4>,
+    /// The decoded revert reason, or `None` if it wasn't decoded.
+    pub reason: Option<RevertReason>,
+}
+
+impl DynFailure {
+    /// Create a new [`DynFailure`] from a [`Failure`] and the call that failed, without decoding
+    /// its revert reason.
+    pub fn new(failure: Failure, call: &DynCallItem) -> Self {
+        Self {
+            idx: failure.idx,
+            return_data: failure.return_data,
+            target: call.target,
+            function: call.decoder.name.clone(),
+            selector: call.decoder.selector(),
+            reason: None,
+        }
+    }
+}
+
+impl std::fmt::Display for DynFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}({}) at index {} reverted",
+            self.function, self.target, self.idx
+        )?;
+
+        match &self.reason {
+            Some(reason) => write!(f, ": {reason}"),
+            None => write!(f, " with {}", self.return_data),
+        }
+    }
+}
+
+impl std::error::Error for DynFailure {}
+
+impl From<DynFailure> for Failure {
+    fn from(failure: DynFailure) -> Self {
+        Self {
+            idx: failure.idx,
+            return_data: failure.return_data,
+        }
+    }
+}
+
+/// The result of a call made by [`DynamicMulticallBuilder::simulate`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimResult {
+    /// The decoded return values, or the [`Failure`] if the call failed.
+    pub result: Result<Vec<DynSolValue>, Failure>,
+    /// The revert reason if the call failed, formatted from [`SimResult::revert`].
+    pub revert_reason: Option<String>,
+    /// The revert reason if the call failed, see [`DynamicMulticallBuilder::revert_reason`].
+    pub revert: Option<RevertReason>,
+}
+
+/// The reason of a revert, decoded from the return data of a failed call
+#[derive(Clone, Debug, PartialEq)]
+pub enum RevertReason {
+    /// An `Error(string)` revert, e.g. from `require` with a message.
+    Error(String),
+    /// A `Panic(uint256)` revert, with its code and description, e.g. `0x11` for an arithmetic
+    /// overflow.
+    Panic(u64, &'static str),
+    /// A revert with any other data, with its selector and the data following it.
+    ///
+    /// Data shorter than a selector is right-padded with zeros, with no data following it.
+    Custom(FixedBytes<4>, Bytes),
+    /// A revert without any data, e.g. from `require` without a message.
+    Empty,
+    /// A custom error with a known definition, with its name and decoded args, see
+    /// [`RevertReason::decode_with_errors`].
+    Decoded(String, Vec<DynSolValue>),
+}
+
+impl RevertReason {
+    /// Decode the reason of a revert from its return data.
+    ///
+    /// `Panic(uint256)` reverts with an unknown code are described as `"unknown panic"`, and
+    /// panic codes overflowing a `u64` are returned as [`RevertReason::Custom`].
+    pub fn decode(data: &[u8]) -> Self {
+        if data.is_empty() {
+            return Self::Empty;
+        }
+
+        if let Ok(revert) = Revert::abi_decode(data) {
+            return Self::Error(revert.reason);
+        }
+
+        if let Ok(panic) = Panic::abi_decode(data) {
+            if let Ok(code) = u64::try_from(panic.code) {
+                let description = panic.kind().map_or("unknown panic", PanicKind::as_str);
+                return Self::Panic(code, description);
+            }
+        }
+
+        match data.split_first_chunk::<4>() {
+            Some((selector, args)) => Self::Custom(selector.into(), Bytes::copy_from_slice(args)),
+            None => Self::Custom(FixedBytes::right_padding_from(data), Bytes::new()),
+        }
+    }
+
+    /// Decode the reason of a revert from its return data, decoding custom errors with the given
+    /// definitions.
+    ///
+    /// `Error(string)` and `Panic(uint256)` reverts are decoded first like in
+    /// [`RevertReason::decode`]. A custom error matching the selector of a definition is returned
+    /// as [`RevertReason::Decoded`], and falls back to [`RevertReason::Custom`] if its args don't
+    /// decode or no definition matches.
+    pub fn decode_with_errors(data: &[u8], errors: &[AbiError]) -> Self {
+        let reason = Self::decode(data);
+
+        let Self::Custom(selector, args) = &reason else {
+            return reason;
+        };
+
+        errors
+            .iter()
+            .filter(|error| error.selector() == *selector)
+            .find_map(|error| {
+                let args = error.abi_decode_input(args).ok()?;
+                Some(Self::Decoded(error.name.clone(), args))
+            })
+            .unwrap_or(reason)
+    }
+}
+
+impl std::fmt::Display for RevertReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Error(reason) => f.write_str(reason),
+            Self::Panic(code, description) => write!(f, "panic {code:#04x}: {description}"),
+            Self::Custom(selector, args) if args.is_empty() => write!(f, "custom error {selector}"),
+            Self::Custom(selector, args) => write!(f, "custom error {selector} with {args}"),
+            Self::Empty => f.write_str("empty revert"),
+            Self::Decoded(name, args) => {
+                write!(f, "{name}(")?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write_value(f, arg)?;
+                }
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+/// Writes a decoded value for a log message, e.g. a `uint256` as a plain number.
+fn write_value(f: &mut std::fmt::Formatter<'_>, value: &DynSolValue) -> std::fmt::Result {
+    match value {
+        DynSolValue::Bool(b) => write!(f, "{b}"),
+        DynSolValue::Int(i, _) => write!(f, "{i}"),
+        DynSolValue::Uint(u, _) => write!(f, "{u}"),
+        DynSolValue::FixedBytes(word, size) => {
+            write!(f, "{}", hex::encode_prefixed(&word[..*size]))
+        }
+        DynSolValue::Address(address) => write!(f, "{address}"),
+        DynSolValue::Bytes(bytes) => write!(f, "{}", hex::encode_prefixed(bytes)),
+        DynSolValue::String(s) => write!(f, "{s:?}"),
+        value => write!(f, "{value:?}"),
+    }
+}
+
+/// An individual multicall call item
+#[derive(Clone)]
+pub struct DynCallItem {
+    target: Address,
+    params: Vec<DynSolValue>,
+    allow_failure: bool,
+    value: U256,
+    decoder: Arc<Function>,
+    label: Option<String>,
+}
+
+impl Debug for DynCallItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CallItem")
+            .field("target", &self.target)
+            .field("allow_failure", &self.allow_failure)
+            .field("value", &self.value)
+            .field("function", &self.decoder.name)
+            .field("params", &self.params)
+            .field("label", &self.label)
+            .finish()
+    }
+}
+
+impl DynCallItem {
+    /// Create a new [`DynCallItem`] instance.
+    ///
+    /// The params aren't validated until the call is encoded, see [`DynCallItem::try_new`].
+    pub fn new(
+        target: Address,
+        params: Vec<DynSolValue>,
+        function: Function,
+        allow_failure: bool,
+    ) -> Self {
+        Self::new_shared(target, params, Arc::new(function), allow_failure)
+    }
+
+    /// Create a new [`DynCallItem`] instance sharing its function with other calls.
+    ///
+    /// Same as [`DynCallItem::new`], but calls created from clones of the same [`Arc`] share a
+    /// single [`Function`], e.g. to save memory in large batches calling the same function on
+    /// many targets.
+    pub const fn new_shared(
+        target: Address,
+        params: Vec<DynSolValue>,
+        function: Arc<Function>,
+        allow_failure: bool,
+    ) -> Self {
+        Self {
+            target,
+            params,
+            allow_failure,
+            value: U256::ZERO,
+            decoder: function,
+            label: None,
+        }
+    }
+
+    /// Create a new [`DynCallItem`] instance, validating the params against the function inputs.
+    ///
+    /// Unlike [`DynCallItem::new`], which defers any mismatch to encoding time, this checks that the
+    /// number of params matches the number of inputs and that each param matches its input type.
+    pub fn try_new(
+        target: Address,
+        params: Vec<DynSolValue>,
+        function: Function,
+        allow_failure: bool,
+    ) -> Result<Self, DynMulticallError> {
+        if params.len() != function.inputs.len() {
+            return Err(DynMulticallError::ParamCountMismatch {
+                expected: function.inputs.len(),
+                got: params.len(),
+            });
+        }
+
+        for (index, (param, input)) in params.iter().zip(&function.inputs).enumerate() {
+            let ty = input.resolve()?;
+
+            if !ty.matches(param) {
+                return Err(DynMulticallError::TypeMismatch {
+                    index,
+                    expected: ty.sol_type_name().into_owned(),
+                    got: param
+                        .sol_type_name()
+                        .map(|name| name.into_owned())
+                        .unwrap_or_else(|| "unknown".to_string()),
+                });
+            }
+        }
+
+        Ok(Self::new(target, params, function, allow_failure))
+    }
+
+    /// Create a new [`DynCallItem`] instance from a human-readable function signature, e.g.
+    /// `"balanceOf(address)(uint256)"`.
+    ///
+    /// The params are validated against the parsed function, see [`DynCallItem::try_new`].
+    pub fn from_signature(
+        target: Address,
+        signature: &str,
+        params: Vec<DynSolValue>,
+        allow_failure: bool,
+    ) -> Result<Self, DynMulticallError> {
+        let function =
+            Function::parse(signature).map_err(|source| DynMulticallError::SignatureParse {
+                signature: signature.to_string(),
+                source,
+            })?;
+
+        Self::try_new(target, params, function, allow_failure)
+    }
+
+    /// Set whether the call should be allowed to fail or not.
+    pub const fn allow_failure(mut self, allow_failure: bool) -> Self {
+        self.allow_failure = allow_failure;
+        self
+    }
+
+    /// Set the value to send with the call.
+    ///
+    /// The value is only forwarded by [`DynamicMulticallBuilder::aggregate3_value`], which sets the
+    /// sum of all call values as the `msg.value` of the multicall.
+    pub const fn value(mut self, value: U256) -> Self {
+        self.value = value;
+        self
+    }
+
+    /// Set a label for the call, returned with its result by
+    /// [`DynamicMulticallBuilder::aggregate3_labeled`].
+    ///
+    /// The label is client-side metadata only and isn't part of the encoded call.
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// ABI-encode the call's params with its function selector.
+    fn encode_input(&self) -> Result<Bytes> {
+        self.decoder
+            .abi_encode_input(&self.params)
+            .map(Into::into)
+            .map_err(|err| {
+                MulticallError::DecodeError(alloy::sol_types::Error::custom(err.to_string()))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::{address, bytes, keccak256, U64},
+        rpc::{json_rpc::ErrorPayload, types::state::StateOverridesBuilder},
+        sol,
+        sol_types::{Panic, Revert, SolError},
+        transports::mock::{Asserter, MockTransport},
+    };
+    use alloy_provider::ProviderBuilder;
+
+    use alloy::providers::bindings::IMulticall3::getBlockHashCall;
+
+    use super::*;
+
+    sol! {
+        #[derive(Debug, PartialEq)]
+        #[sol(rpc, abi)]
+        interface ERC20 {
+            function totalSupply() external view returns (uint256 totalSupply);
+            function balanceOf(address owner) external view returns (uint256 balance);
+            function transfer(address to, uint256 value) external returns (bool);
+        }
+
+        #[derive(Debug, PartialEq)]
+        #[sol(rpc, abi)]
+        interface WETH {
+            function deposit() external payable;
+        }
+    }
+
+    const FORK_URL: &str = "https://reth-ethereum.ithaca.xyz/rpc";
+
+    const WETH: Address = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+
+    /// Returns a `totalSupply()` call to [`WETH`].
+    fn total_supply_call(allow_failure: bool) -> DynCallItem {
+        DynCallItem::from_signature(WETH, "totalSupply()(uint256)", Vec::new(), allow_failure)
+            .unwrap()
+    }
+
+    /// Returns the definition of an [`ERC20`] function.
+    fn erc20_function(name: &str) -> Function {
+        ERC20::abi::functions()[name][0].clone()
+    }
+
+    /// Returns an asserter with a provider answering with its responses.
+    fn mock_provider() -> (Asserter, impl Provider + Clone) {
+        let asserter = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+
+        (asserter, provider)
+    }
+
+    /// Returns an asserter with a builder of `n` [`total_supply_call`]s answered by it.
+    fn mock_builder(
+        n: usize,
+        allow_failure: bool,
+    ) -> (
+        Asserter,
+        DynamicMulticallBuilder<impl Provider + Clone, alloy::network::Ethereum>,
+    ) {
+        let (asserter, provider) = mock_provider();
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_calls(std::iter::repeat_n(total_supply_call(allow_failure), n));
+
+        (asserter, dynamic_multicall)
+    }
+
+    /// Encodes an `aggregate3` response returning a `uint256` for each `(success, value)`.
+    fn aggregate3_response(results: impl IntoIterator<Item = (bool, u64)>) -> Bytes {
+        let results = results
+            .into_iter()
+            .map(|(success, value)| MulticallResult {
+                success,
+                returnData: U256::from(value).to_be_bytes::<32>().into(),
+            })
+            .collect::<Vec<_>>();
+
+        aggregate3Call::abi_encode_returns(&results).into()
+    }
+
+    /// Pushes an [`aggregate3_response`] to the asserter.
+    fn push_results(asserter: &Asserter, results: impl IntoIterator<Item = (bool, u64)>) {
+        asserter.push_success(&aggregate3_response(results));
+    }
+
+    /// The code of a mocked failure returned as a transport error by a [`FlakyTransport`].
+    const TRANSPORT_FAILURE: i64 = i64::MIN;
+
+    /// Pushes a failure returned as a transport error, e.g. a dropped connection, by a provider
+    /// connected with [`connect_flaky`].
+    fn push_transport_failure(asserter: &Asserter, message: &'static str) {
+        asserter.push_failure(ErrorPayload {
+            code: TRANSPORT_FAILURE,
+            message: message.into(),
+            data: None,
+        });
+    }
+
+    /// A mock transport returning the failures pushed with [`push_transport_failure`] as
+    /// transport errors rather than error responses.
+    #[derive(Clone, Debug)]
+    struct FlakyTransport(MockTransport);
+
+    impl tower::Service<alloy::rpc::json_rpc::RequestPacket> for FlakyTransport {
+        type Response = alloy::rpc::json_rpc::ResponsePacket;
+        type Error = TransportError;
+        type Future = alloy::transports::TransportFut<'static>;
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            self.0.poll_ready(cx)
+        }
+
+        fn call(&mut self, req: alloy::rpc::json_rpc::RequestPacket) -> Self::Future {
+            let mut responses = self.0.write_q();
+
+            match responses.front() {
+                Some(alloy::rpc::json_rpc::ResponsePayload::Failure(err))
+                    if err.code == TRANSPORT_FAILURE =>
+                {
+                    let err = TransportErrorKind::custom_str(&err.message);
+                    responses.pop_front();
+                    Box::pin(async move { Err(err) })
+                }
+                _ => {
+                    drop(responses);
+                    self.0.call(req)
+                }
+            }
+        }
+    }
+
+    /// Connects a provider to a [`FlakyTransport`] answering with the responses of `asserter`.
+    fn connect_flaky(asserter: &Asserter) -> impl Provider + Clone {
+        ProviderBuilder::new().connect_client(alloy::rpc::client::RpcClient::new(
+            FlakyTransport(MockTransport::new(asserter.clone())),
+            true,
+        ))
+    }
+
+    /// The method and params of each request sent through a [`RecordingTransport`].
+    type RecordedRequests = Arc<std::sync::Mutex<Vec<(String, serde_json::Value)>>>;
+
+    /// A mock transport recording the requests it's sent, to assert on what is actually
+    /// serialized.
+    #[derive(Clone, Debug)]
+    struct RecordingTransport(MockTransport, RecordedRequests);
+
+    impl tower::Service<alloy::rpc::json_rpc::RequestPacket> for RecordingTransport {
+        type Response = alloy::rpc::json_rpc::ResponsePacket;
+        type Error = TransportError;
+        type Future = alloy::transports::TransportFut<'static>;
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            self.0.poll_ready(cx)
+        }
+
+        fn call(&mut self, req: alloy::rpc::json_rpc::RequestPacket) -> Self::Future {
+            self.1
+                .lock()
+                .unwrap()
+                .extend(req.requests().iter().map(|req| {
+                    let params = req.params().map_or(serde_json::Value::Null, |p| {
+                        serde_json::from_str(p.get()).unwrap()
+                    });
+                    (req.method().to_string(), params)
+                }));
+
+            self.0.call(req)
+        }
+    }
+
+    /// Connects a provider to a [`RecordingTransport`] answering with the responses of
+    /// `asserter`.
+    fn connect_recording(asserter: &Asserter) -> (impl Provider + Clone, RecordedRequests) {
+        let requests = RecordedRequests::default();
+        let provider = ProviderBuilder::new().connect_client(alloy::rpc::client::RpcClient::new(
+            RecordingTransport(MockTransport::new(asserter.clone()), requests.clone()),
+            true,
+        ));
+
+        (provider, requests)
+    }
+
+    /// Returns true if the state overrides of a recorded request set the code of `address`.
+    fn overrides_code(params: &serde_json::Value, address: Address) -> bool {
+        let address = format!("{address:#x}");
+
+        params.as_array().unwrap().iter().skip(1).any(|param| {
+            param.as_object().is_some_and(|overrides| {
+                overrides.iter().any(|(key, account)| {
+                    key.eq_ignore_ascii_case(&address) && account["code"].is_string()
+                })
+            })
+        })
+    }
+
+    /// A transport answering each `aggregate3` `eth_call` from its calldata after a delay, so
+    /// concurrent chunks complete out of order.
+    ///
+    /// Each `balanceOf(owner)` call returns the owner as a `uint256`, and fails if the last byte
+    /// of the owner is a multiple of 5. Chunks starting at a higher owner answer sooner.
+    #[derive(Clone, Debug, Default)]
+    struct DelayedTransport {
+        in_flight: Arc<AtomicUsize>,
+        max_in_flight: Arc<AtomicUsize>,
+    }
+
+    impl tower::Service<alloy::rpc::json_rpc::RequestPacket> for DelayedTransport {
+        type Response = alloy::rpc::json_rpc::ResponsePacket;
+        type Error = TransportError;
+        type Future = alloy::transports::TransportFut<'static>;
+
+        fn poll_ready(
+            &mut self,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: alloy::rpc::json_rpc::RequestPacket) -> Self::Future {
+            let alloy::rpc::json_rpc::RequestPacket::Single(req) = req else {
+                panic!("unexpected batch request");
+            };
+            let in_flight = self.in_flight.clone();
+            let max_in_flight = self.max_in_flight.clone();
+
+            Box::pin(async move {
+                let (result, delay) = match req.method() {
+                    "eth_blockNumber" => (
+                        serde_json::value::to_raw_value(&U64::from(20_000_000)).unwrap(),
+                        Duration::ZERO,
+                    ),
+                    "eth_call" => {
+                        let params: serde_json::Value =
+                            serde_json::from_str(req.params().unwrap().get()).unwrap();
+                        let tx = &params[0];
+                        let input: Bytes =
+                            serde_json::from_value(tx.get("input").unwrap_or(&tx["data"]).clone())
+                                .unwrap();
+                        let call = aggregate3Call::abi_decode(&input).unwrap();
+
+                        let results = call
+                            .calls
+                            .iter()
+                            .map(|call| {
+                                let owner = U256::from_be_slice(&call.callData[4..]);
+                                MulticallResult {
+                                    success: owner % U256::from(5) != U256::ZERO,
+                                    returnData: owner.to_be_bytes::<32>().into(),
+                                }
+                            })
+                            .collect::<Vec<_>>();
+                        let first = U256::from_be_slice(&call.calls[0].callData[4..]);
+                        let delay = Duration::from_millis(1_000 - first.to::<u64>() * 10);
+
+                        (
+                            serde_json::value::to_raw_value(&Bytes::from(
+                                aggregate3Call::abi_encode_returns(&results),
+                            ))
+                            .unwrap(),
+                            delay,
+                        )
+                    }
+                    method => panic!("unexpected request {method}"),
+                };
+
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(delay).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+
+                Ok(alloy::rpc::json_rpc::ResponsePacket::Single(
+                    alloy::rpc::json_rpc::Response {
+                        id: req.id().clone(),
+                        payload: alloy::rpc::json_rpc::ResponsePayload::Success(result),
+                    },
+                ))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_multicaller() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let weth = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+        let provider = ProviderBuilder::new().connect_anvil_with_config(|a| a.fork(FORK_URL));
+
+        let total_supply_function = ERC20::abi::functions()
+            .get("totalSupply")
+            .cloned()
+            .unwrap()
+            .first()
+            .unwrap()
+            .clone();
+
+        let balance_of_function = ERC20::abi::functions()
+            .get("balanceOf")
+            .cloned()
+            .unwrap()
+            .first()
+            .unwrap()
+            .clone();
+
+        let balance_of_call_item = DynCallItem::new(
+            weth,
+            vec![DynSolValue::Address(address!(
+                "d8dA6BF26964aF9D7eEd9e03E53415D37aA96045"
+            ))],
+            balance_of_function,
+            false,
+        );
+
+        let total_supply_call_item =
+            DynCallItem::new(weth, Vec::new(), total_supply_function, false);
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider.clone())
+            .add_call(balance_of_call_item)
+            .add_call(total_supply_call_item);
+
+        assert_eq!(dynamic_multicall.len(), 2);
+
+        let res = dynamic_multicall.aggregate3().await.unwrap();
+
+        assert_eq!(res.len(), 2);
+
+        for result in res {
+            let decoded = result.unwrap();
+            assert_eq!(decoded.len(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_multicaller_with_many() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let weth = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+        let provider = ProviderBuilder::new().connect_anvil_with_config(|a| a.fork(FORK_URL));
+
+        let total_supply_function = ERC20::abi::functions()
+            .get("totalSupply")
+            .cloned()
+            .unwrap()
+            .first()
+            .unwrap()
+            .clone();
+
+        let balance_of_function = ERC20::abi::functions()
+            .get("balanceOf")
+            .cloned()
+            .unwrap()
+            .first()
+            .unwrap()
+            .clone();
+
+        let balance_of_call_item = DynCallItem::new(
+            weth,
+            vec![DynSolValue::Address(address!(
+                "d8dA6BF26964aF9D7eEd9e03E53415D37aA96045"
+            ))],
+            balance_of_function,
+            false,
+        );
+
+        let total_supply_call_item =
+            DynCallItem::new(weth, Vec::new(), total_supply_function, false);
+
+        let mut dynamic_multicall = DynamicMulticallBuilder::new(provider.clone());
+
+        for _ in 0..20 {
+            dynamic_multicall = dynamic_multicall
+                .add_call(balance_of_call_item.clone())
+                .add_call(total_supply_call_item.clone());
+        }
+
+        assert_eq!(dynamic_multicall.len(), 40);
+
+        let res = dynamic_multicall.aggregate3().await.unwrap();
+
+        assert_eq!(res.len(), 40);
+
+        for result in res {
+            let decoded = result.unwrap();
+            assert_eq!(decoded.len(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_multicaller_at_block() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let provider = ProviderBuilder::new().connect_anvil_with_config(|a| a.fork(FORK_URL));
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let total_supply_call_item =
+            DynCallItem::new(WETH, Vec::new(), total_supply_function, false);
+
+        let historical = DynamicMulticallBuilder::new(provider.clone())
+            .add_call(total_supply_call_item.clone())
+            .with_block(20_000_000u64);
+
+        assert_eq!(historical.block(), Some(BlockId::number(20_000_000)));
+
+        let latest =
+            DynamicMulticallBuilder::new(provider.clone()).add_call(total_supply_call_item);
+
+        let historical_res = historical.aggregate3().await.unwrap();
+        let latest_res = latest.aggregate3().await.unwrap();
+
+        assert_eq!(historical_res.len(), 1);
+        assert_eq!(latest_res.len(), 1);
+
+        let historical_supply = historical_res[0].as_ref().unwrap()[0].as_uint().unwrap().0;
+        let latest_supply = latest_res[0].as_ref().unwrap()[0].as_uint().unwrap().0;
+
+        assert_ne!(historical_supply, latest_supply);
+    }
+
+    #[tokio::test]
+    async fn test_contract_with_fallback() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let forwarder_contract = address!("0xd5fe1c1f216b775dfd30638fa7164d41321ef79b");
+        let provider = ProviderBuilder::new().connect_anvil_with_config(|a| a.fork(FORK_URL));
+
+        let total_supply_function = ERC20::abi::functions()
             .get("totalSupply")
             .cloned()
             .unwrap()
-            .first()
+            .first()
+            .unwrap()
+            .clone();
+
+        let balance_of_function = ERC20::abi::functions()
+            .get("balanceOf")
+            .cloned()
+            .unwrap()
+            .first()
+            .unwrap()
+            .clone();
+
+        let balance_of_call_item = DynCallItem::new(
+            forwarder_contract,
+            vec![DynSolValue::Address(address!(
+                "d8dA6BF26964aF9D7eEd9e03E53415D37aA96045"
+            ))],
+            balance_of_function,
+            false,
+        );
+
+        let total_supply_call_item =
+            DynCallItem::new(forwarder_contract, Vec::new(), total_supply_function, false);
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider.clone())
+            .add_call(balance_of_call_item)
+            .add_call(total_supply_call_item);
+
+        assert_eq!(dynamic_multicall.len(), 2);
+
+        let res = dynamic_multicall.aggregate3().await.unwrap();
+
+        assert_eq!(res.len(), 2);
+
+        for result in res {
+            let err = result.expect_err("should have errored");
+            assert!(err.return_data.is_empty())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_value() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let provider = ProviderBuilder::new().connect_anvil_with_config(|a| a.fork(FORK_URL));
+
+        let deposit_function = WETH::abi::functions()
+            .get("deposit")
+            .cloned()
+            .unwrap()
+            .first()
+            .unwrap()
+            .clone();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider.clone())
+            .add_call(
+                DynCallItem::new(WETH, Vec::new(), deposit_function.clone(), false)
+                    .value(U256::from(1)),
+            )
+            .add_call(DynCallItem::new(
+                WETH,
+                Vec::new(),
+                total_supply_function,
+                false,
+            ))
+            .add_call(
+                DynCallItem::new(WETH, Vec::new(), deposit_function, false).value(U256::from(2)),
+            );
+
+        let res = dynamic_multicall.aggregate3_value().await.unwrap();
+
+        assert_eq!(res.len(), 3);
+
+        assert!(res[0].as_ref().unwrap().is_empty());
+        assert_eq!(res[1].as_ref().unwrap().len(), 1);
+        assert!(res[2].as_ref().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_value_overflow() {
+        let (_, provider) = mock_provider();
+
+        let deposit_function = WETH::abi::functions()
+            .get("deposit")
+            .cloned()
+            .unwrap()
+            .first()
+            .unwrap()
+            .clone();
+
+        let call_item =
+            DynCallItem::new(WETH, Vec::new(), deposit_function, false).value(U256::MAX);
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_call(call_item.clone())
+            .add_call(call_item);
+
+        let err = dynamic_multicall.aggregate3_value().await.unwrap_err();
+
+        assert!(matches!(err, DynMulticallError::ValueOverflow));
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_multicaller_with_state_override() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let target = address!("0000000000000000000000000000000000001234");
+        let provider = ProviderBuilder::new().connect_anvil_with_config(|a| a.fork(FORK_URL));
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        // PUSH1 0x2a PUSH1 0x00 MSTORE PUSH1 0x20 PUSH1 0x00 RETURN
+        let overrides = StateOverridesBuilder::default()
+            .with_code(target, bytes!("602a60005260206000f3"))
+            .build();
+
+        let mut dynamic_multicall = DynamicMulticallBuilder::new(provider.clone())
+            .add_call(DynCallItem::new(
+                target,
+                Vec::new(),
+                total_supply_function,
+                false,
+            ))
+            .with_state_override(overrides);
+
+        assert!(dynamic_multicall.state_override_mut().is_some());
+
+        let res = dynamic_multicall.aggregate3().await.unwrap();
+
+        assert_eq!(res.len(), 1);
+        assert_eq!(
+            res[0].as_ref().unwrap()[0],
+            DynSolValue::Uint(U256::from(42), 256)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_rejects_value() {
+        let (asserter, provider) = mock_provider();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let call_item =
+            DynCallItem::new(WETH, Vec::new(), total_supply_function, false).value(U256::from(1));
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider).add_call(call_item);
+
+        let err = dynamic_multicall.aggregate3().await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "calls [0] have a value, which aggregate3 can't forward"
+        );
+        assert!(matches!(err, DynMulticallError::ValueInAggregate3 { indices } if indices == [0]));
+
+        let dynamic_multicall = dynamic_multicall.allow_value_in_aggregate3(true);
+
+        push_results(&asserter, [(true, 42)]);
+
+        let res = dynamic_multicall.aggregate3().await.unwrap();
+        assert_eq!(
+            res[0].as_ref().unwrap()[0],
+            DynSolValue::Uint(U256::from(42), 256)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_try_aggregate() {
+        let (asserter, provider) = mock_provider();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let call_item = DynCallItem::new(WETH, Vec::new(), total_supply_function, false);
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_call(call_item.clone())
+            .add_call(call_item);
+
+        asserter.push_success(&Bytes::from(tryAggregateCall::abi_encode_returns(&vec![
+            MulticallResult {
+                success: true,
+                returnData: U256::from(42).to_be_bytes::<32>().into(),
+            },
+            MulticallResult {
+                success: false,
+                returnData: Bytes::new(),
+            },
+        ])));
+
+        let res = dynamic_multicall.try_aggregate(false).await.unwrap();
+
+        assert_eq!(res.len(), 2);
+        assert_eq!(
+            res[0].as_ref().unwrap()[0],
+            DynSolValue::Uint(U256::from(42), 256)
+        );
+        assert_eq!(res[1].as_ref().unwrap_err().idx, 1);
+    }
+
+    #[tokio::test]
+    async fn test_aggregate() {
+        let (asserter, provider) = mock_provider();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let call_item = DynCallItem::new(WETH, Vec::new(), total_supply_function, false);
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_call(call_item.clone())
+            .add_call(call_item);
+
+        asserter.push_success(&Bytes::from(aggregateCall::abi_encode_returns(
+            &aggregateReturn {
+                blockNumber: U256::from(20_000_000),
+                returnData: vec![
+                    U256::from(1).to_be_bytes::<32>().into(),
+                    U256::from(2).to_be_bytes::<32>().into(),
+                ],
+            },
+        )));
+
+        let (block_number, res) = dynamic_multicall.aggregate().await.unwrap();
+
+        assert_eq!(block_number, 20_000_000);
+        assert_eq!(
+            res,
+            vec![
+                vec![DynSolValue::Uint(U256::from(1), 256)],
+                vec![DynSolValue::Uint(U256::from(2), 256)],
+            ]
+        );
+
+        asserter.push_success(&Bytes::from(aggregateCall::abi_encode_returns(
+            &aggregateReturn {
+                blockNumber: U256::from(20_000_000),
+                returnData: vec![U256::from(1).to_be_bytes::<32>().into(), Bytes::new()],
+            },
+        )));
+
+        let err = dynamic_multicall.aggregate().await.unwrap_err();
+        assert!(matches!(
+            err,
+            DynMulticallError::Decode { idx: 1, target, ref return_data, .. }
+                if target == WETH && return_data.is_empty()
+        ));
+
+        // A block number overflowing a u64 is an error rather than a panic.
+        asserter.push_success(&Bytes::from(aggregateCall::abi_encode_returns(
+            &aggregateReturn {
+                blockNumber: U256::MAX,
+                returnData: vec![
+                    U256::from(1).to_be_bytes::<32>().into(),
+                    U256::from(2).to_be_bytes::<32>().into(),
+                ],
+            },
+        )));
+
+        assert!(matches!(
+            dynamic_multicall.aggregate().await,
+            Err(DynMulticallError::BlockNumberOverflow(n)) if n == U256::MAX
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_revert() {
+        let (asserter, provider) = mock_provider();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider).add_call(DynCallItem::new(
+            WETH,
+            Vec::new(),
+            total_supply_function,
+            false,
+        ));
+
+        let revert_data = bytes!("08c379a0");
+
+        asserter.push_failure(
+            ErrorPayload::internal_error_with_message_and_obj(
+                "execution reverted".into(),
+                revert_data.clone(),
+            )
+            .serialize_payload()
+            .unwrap(),
+        );
+
+        let err = dynamic_multicall.aggregate().await.unwrap_err();
+
+        assert!(matches!(
+            err,
+            DynMulticallError::Multicall(MulticallError::CallFailed(data)) if data == revert_data
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_multicaller_aggregate() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let provider = ProviderBuilder::new().connect_anvil_with_config(|a| a.fork(FORK_URL));
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let balance_of_function = erc20_function("balanceOf");
+
+        let balance_of_call_item = DynCallItem::new(
+            WETH,
+            vec![DynSolValue::Address(address!(
+                "d8dA6BF26964aF9D7eEd9e03E53415D37aA96045"
+            ))],
+            balance_of_function,
+            false,
+        );
+
+        let total_supply_call_item =
+            DynCallItem::new(WETH, Vec::new(), total_supply_function, false);
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider.clone())
+            .add_call(balance_of_call_item)
+            .add_call(total_supply_call_item);
+
+        let (block_number, res) = dynamic_multicall.aggregate().await.unwrap();
+
+        assert!(block_number > 0);
+        assert_eq!(res.len(), 2);
+
+        for decoded in res {
+            assert_eq!(decoded.len(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_multicaller_try_aggregate() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let non_contract = address!("d8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
+        let provider = ProviderBuilder::new().connect_anvil_with_config(|a| a.fork(FORK_URL));
+
+        let balance_of_function = erc20_function("balanceOf");
+
+        let transfer_function = erc20_function("transfer");
+
+        let params = vec![DynSolValue::Address(non_contract)];
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider.clone())
+            .add_call(DynCallItem::new(
+                WETH,
+                params.clone(),
+                balance_of_function.clone(),
+                false,
+            ))
+            .add_call(DynCallItem::new(
+                non_contract,
+                params,
+                balance_of_function,
+                false,
+            ));
+
+        let res = dynamic_multicall.try_aggregate(false).await.unwrap();
+
+        assert_eq!(res.len(), 2);
+        assert!(res[0].is_ok());
+        assert_eq!(res[1].as_ref().unwrap_err().idx, 1);
+
+        // The multicall contract holds no WETH, so the transfer reverts.
+        let dynamic_multicall = dynamic_multicall.add_call(DynCallItem::new(
+            WETH,
+            vec![
+                DynSolValue::Address(non_contract),
+                DynSolValue::Uint(U256::MAX, 256),
+            ],
+            transfer_function,
+            false,
+        ));
+
+        let err = dynamic_multicall.try_aggregate(true).await.unwrap_err();
+
+        assert!(matches!(
+            err,
+            DynMulticallError::Multicall(MulticallError::CallFailed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_block_and_aggregate() {
+        let (asserter, provider) = mock_provider();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider).add_call(DynCallItem::new(
+            WETH,
+            Vec::new(),
+            total_supply_function,
+            false,
+        ));
+
+        let block_hash = B256::repeat_byte(0xab);
+
+        asserter.push_success(&Bytes::from(blockAndAggregateCall::abi_encode_returns(
+            &blockAndAggregateReturn {
+                blockNumber: U256::from(20_000_000),
+                blockHash: block_hash,
+                returnData: vec![MulticallResult {
+                    success: true,
+                    returnData: U256::from(42).to_be_bytes::<32>().into(),
+                }],
+            },
+        )));
+
+        let (block_number, hash, res) = dynamic_multicall.block_and_aggregate().await.unwrap();
+
+        assert_eq!(block_number, 20_000_000);
+        assert_eq!(hash, block_hash);
+        assert_eq!(
+            res[0].as_ref().unwrap()[0],
+            DynSolValue::Uint(U256::from(42), 256)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_multicaller_block_and_aggregate() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let provider = ProviderBuilder::new().connect_anvil_with_config(|a| a.fork(FORK_URL));
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider.clone())
+            .add_call(DynCallItem::new(
+                WETH,
+                Vec::new(),
+                total_supply_function,
+                false,
+            ))
+            .with_block(20_000_000u64);
+
+        let (block_number, _, res) = dynamic_multicall.block_and_aggregate().await.unwrap();
+
+        assert_eq!(block_number, 20_000_000);
+        assert_eq!(res.len(), 1);
+        assert!(res[0].is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_try_block_and_aggregate() {
+        let (asserter, provider) = mock_provider();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let call_item = DynCallItem::new(WETH, Vec::new(), total_supply_function, false);
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_call(call_item.clone())
+            .add_call(call_item);
+
+        let block_hash = B256::repeat_byte(0xab);
+
+        asserter.push_success(&Bytes::from(tryBlockAndAggregateCall::abi_encode_returns(
+            &tryBlockAndAggregateReturn {
+                blockNumber: U256::from(20_000_000),
+                blockHash: block_hash,
+                returnData: vec![
+                    MulticallResult {
+                        success: false,
+                        returnData: bytes!("08c379a0"),
+                    },
+                    MulticallResult {
+                        success: true,
+                        returnData: U256::from(42).to_be_bytes::<32>().into(),
+                    },
+                ],
+            },
+        )));
+
+        let (block_number, hash, res) = dynamic_multicall
+            .try_block_and_aggregate(false)
+            .await
+            .unwrap();
+
+        assert_eq!(block_number, 20_000_000);
+        assert_eq!(hash, block_hash);
+        assert_eq!(
+            res[0].as_ref().unwrap_err(),
+            &Failure {
+                idx: 0,
+                return_data: bytes!("08c379a0"),
+            }
+        );
+        assert_eq!(
+            res[1].as_ref().unwrap()[0],
+            DynSolValue::Uint(U256::from(42), 256)
+        );
+    }
+
+    #[test]
+    fn test_with_address() {
+        let multicall = address!("0000000000000000000000000000000000001234");
+        let (_, provider) = mock_provider();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider);
+        assert_eq!(dynamic_multicall.address(), MULTICALL3_ADDRESS);
+
+        let dynamic_multicall = dynamic_multicall.with_address(multicall).clear();
+        assert_eq!(dynamic_multicall.address(), multicall);
+
+        let tx = dynamic_multicall.build_tx(&aggregate3Call { calls: Vec::new() }, None);
+        assert_eq!(TransactionBuilder::to(&tx), Some(multicall));
+    }
+
+    #[tokio::test]
+    async fn test_add_get_eth_balance() {
+        let multicall = address!("0000000000000000000000000000000000001234");
+        let owner = address!("d8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
+        let (asserter, provider) = mock_provider();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .with_address(multicall)
+            .add_get_eth_balance(owner)
+            .add_get_eth_balances([Address::ZERO, owner]);
+
+        assert_eq!(dynamic_multicall.len(), 3);
+        assert!(dynamic_multicall
+            .calls
+            .iter()
+            .all(|c| c.target == multicall));
+        assert_eq!(
+            dynamic_multicall.calls[0].encode_input().unwrap(),
+            Bytes::from(getEthBalanceCall { addr: owner }.abi_encode())
+        );
+
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(
+            &(1..=3)
+                .map(|balance| MulticallResult {
+                    success: true,
+                    returnData: U256::from(balance).to_be_bytes::<32>().into(),
+                })
+                .collect(),
+        )));
+
+        let res = dynamic_multicall.aggregate3().await.unwrap();
+
+        assert_eq!(
+            res[2].as_ref().unwrap(),
+            &vec![DynSolValue::Uint(U256::from(3), 256)]
+        );
+    }
+
+    #[test]
+    fn test_add_calls() {
+        let (_, provider) = mock_provider();
+
+        let balance_of_function = erc20_function("balanceOf");
+
+        let calls = (0..50u8).map(|i| {
+            DynCallItem::new(
+                WETH,
+                vec![DynSolValue::Address(Address::with_last_byte(i))],
+                balance_of_function.clone(),
+                false,
+            )
+        });
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider).add_calls(calls);
+
+        assert_eq!(dynamic_multicall.len(), 50);
+    }
+
+    #[tokio::test]
+    async fn test_add_block_utility_calls() {
+        let multicall = address!("0000000000000000000000000000000000001234");
+        let (asserter, provider) = mock_provider();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .with_address(multicall)
+            .add_get_block_number()
+            .add_get_chain_id()
+            .add_get_block_hash(20_000_000)
+            .add_get_current_block_timestamp()
+            .add_get_current_block_coinbase()
+            .add_get_current_block_gas_limit()
+            .add_get_current_block_difficulty()
+            .add_get_base_fee()
+            .add_get_last_block_hash();
+
+        assert_eq!(dynamic_multicall.len(), 9);
+        assert!(dynamic_multicall
+            .calls
+            .iter()
+            .all(|c| c.target == multicall));
+        assert_eq!(
+            dynamic_multicall.calls[2].encode_input().unwrap(),
+            Bytes::from(
+                getBlockHashCall {
+                    blockNumber: U256::from(20_000_000)
+                }
+                .abi_encode()
+            )
+        );
+
+        let dynamic_multicall = dynamic_multicall
+            .clear()
+            .add_get_block_number()
+            .add_get_chain_id();
+
+        push_results(&asserter, [(true, 20_000_000), (true, 1)]);
+
+        let res = dynamic_multicall.aggregate3().await.unwrap();
+
+        assert_eq!(
+            res[0].as_ref().unwrap(),
+            &vec![DynSolValue::Uint(U256::from(20_000_000), 256)]
+        );
+        assert_eq!(
+            res[1].as_ref().unwrap(),
+            &vec![DynSolValue::Uint(U256::from(1), 256)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_multicaller_at_two_blocks() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let provider = ProviderBuilder::new().connect_anvil_with_config(|a| a.fork(FORK_URL));
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let total_supply_call_item =
+            DynCallItem::new(WETH, Vec::new(), total_supply_function, false);
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider.clone())
+            .add_call(total_supply_call_item.clone())
+            .with_block(19_000_000u64);
+
+        let first = dynamic_multicall.aggregate3().await.unwrap();
+
+        let dynamic_multicall = dynamic_multicall.clear();
+        assert_eq!(dynamic_multicall.block(), Some(BlockId::number(19_000_000)));
+
+        let second = dynamic_multicall
+            .add_call(total_supply_call_item)
+            .with_block(20_000_000u64)
+            .aggregate3()
+            .await
+            .unwrap();
+
+        assert_ne!(first[0].as_ref().unwrap(), second[0].as_ref().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_chunked() {
+        let (asserter, provider) = mock_provider();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let call_item = DynCallItem::new(WETH, Vec::new(), total_supply_function, true);
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider);
+
+        // An empty builder makes no request.
+        assert_eq!(
+            dynamic_multicall.aggregate3_chunked(2).await.unwrap(),
+            (None, Vec::new())
+        );
+
+        let dynamic_multicall = dynamic_multicall.add_calls(std::iter::repeat_n(call_item, 5));
+
+        assert!(matches!(
+            dynamic_multicall.aggregate3_chunked(0).await,
+            Err(DynMulticallError::EmptyChunkSize)
+        ));
+
+        let success = |value: u64| MulticallResult {
+            success: true,
+            returnData: U256::from(value).to_be_bytes::<32>().into(),
+        };
+
+        // The chunks are pinned to the current block.
+        asserter.push_success(&U64::from(1));
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+            success(0),
+            success(1),
+        ])));
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+            success(2),
+            success(3),
+        ])));
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+            MulticallResult {
+                success: false,
+                returnData: Bytes::new(),
+            },
+        ])));
+
+        let (block, res) = dynamic_multicall.aggregate3_chunked(2).await.unwrap();
+
+        assert_eq!(block, Some(1));
+        assert_eq!(res.len(), 5);
+        for (idx, result) in res.iter().take(4).enumerate() {
+            assert_eq!(
+                result.as_ref().unwrap(),
+                &vec![DynSolValue::Uint(U256::from(idx), 256)]
+            );
+        }
+        assert_eq!(res[4].as_ref().unwrap_err().idx, 4);
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_concurrent() {
+        let (asserter, provider) = mock_provider();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let call_item = DynCallItem::new(WETH, Vec::new(), total_supply_function, false);
+
+        let dynamic_multicall =
+            DynamicMulticallBuilder::new(provider).add_calls(std::iter::repeat_n(call_item, 200));
+
+        assert!(matches!(
+            dynamic_multicall.aggregate3_concurrent(20, 0).await,
+            Err(DynMulticallError::ZeroConcurrency)
+        ));
+
+        asserter.push_success(&U64::from(1));
+        for chunk in 0..10u64 {
+            asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(
+                &(chunk * 20..(chunk + 1) * 20)
+                    .map(|value| MulticallResult {
+                        success: true,
+                        returnData: U256::from(value).to_be_bytes::<32>().into(),
+                    })
+                    .collect(),
+            )));
+        }
+
+        let (block, res) = dynamic_multicall
+            .aggregate3_concurrent(20, 4)
+            .await
+            .unwrap();
+
+        assert_eq!(block, Some(1));
+        assert_eq!(res.len(), 200);
+        for (idx, result) in res.iter().enumerate() {
+            assert_eq!(
+                result.as_ref().unwrap(),
+                &vec![DynSolValue::Uint(U256::from(idx), 256)]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_multicaller_at_block_number_and_hash() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let provider = ProviderBuilder::new().connect_anvil_with_config(|a| a.fork(FORK_URL));
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider.clone())
+            .add_call(DynCallItem::new(
+                WETH,
+                Vec::new(),
+                total_supply_function,
+                false,
+            ))
+            .at_block_number(20_000_000);
+
+        assert_eq!(dynamic_multicall.block(), Some(BlockId::number(20_000_000)));
+
+        let by_number = dynamic_multicall.aggregate3().await.unwrap();
+
+        let block_hash = provider
+            .get_block_by_number(20_000_000.into())
+            .await
+            .unwrap()
+            .unwrap()
+            .header
+            .hash;
+
+        let dynamic_multicall = dynamic_multicall.at_block_hash(block_hash);
+
+        assert_eq!(dynamic_multicall.block(), Some(BlockId::hash(block_hash)));
+
+        let by_hash = dynamic_multicall.aggregate3().await.unwrap();
+
+        assert_eq!(by_number[0].as_ref().unwrap(), by_hash[0].as_ref().unwrap());
+
+        let err = dynamic_multicall
+            .at_block_hash(B256::repeat_byte(0xab))
+            .aggregate3()
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            DynMulticallError::Multicall(MulticallError::TransportError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_estimate_gas() {
+        let (asserter, provider) = mock_provider();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider).add_call(DynCallItem::new(
+            WETH,
+            Vec::new(),
+            total_supply_function,
+            false,
+        ));
+
+        asserter.push_success(&U64::from(30_000));
+
+        assert_eq!(dynamic_multicall.estimate_gas().await.unwrap(), 30_000);
+
+        // Node errors are surfaced.
+        asserter.push_failure_msg("state overrides not supported");
+        let err = dynamic_multicall
+            .clone()
+            .with_injected_multicall()
+            .estimate_gas()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("state overrides not supported"));
+
+        // Value-bearing calls are rejected without a request.
+        let err = dynamic_multicall
+            .add_call(
+                DynCallItem::from_signature(WETH, "deposit()", Vec::new(), false)
+                    .unwrap()
+                    .value(U256::from(1)),
+            )
+            .estimate_gas()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DynMulticallError::ValueInAggregate3 { indices } if indices == [1]));
+        assert!(asserter.read_q().is_empty());
+
+        // The call gas limit and fee fields are part of the estimated request.
+        let (recording_provider, requests) = connect_recording(&asserter);
+        let dynamic_multicall = DynamicMulticallBuilder::new(recording_provider)
+            .add_call(total_supply_call(false))
+            .with_call_gas_limit(1_000_000)
+            .with_max_fee_per_gas(2_000_000_000);
+        asserter.push_success(&U64::from(30_000));
+        assert_eq!(dynamic_multicall.estimate_gas().await.unwrap(), 30_000);
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].0, "eth_estimateGas");
+        assert_eq!(requests[0].1[0]["gas"], "0xf4240");
+        assert_eq!(requests[0].1[0]["maxFeePerGas"], "0x77359400");
+    }
+
+    #[test]
+    fn test_block_tags() {
+        let (_, provider) = mock_provider();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider);
+        assert_eq!(dynamic_multicall.block(), None);
+
+        let dynamic_multicall = dynamic_multicall.at_latest();
+        assert_eq!(dynamic_multicall.block(), Some(BlockId::latest()));
+
+        let dynamic_multicall = dynamic_multicall.at_pending();
+        assert_eq!(dynamic_multicall.block(), Some(BlockId::pending()));
+
+        let dynamic_multicall = dynamic_multicall.at_safe();
+        assert_eq!(dynamic_multicall.block(), Some(BlockId::safe()));
+
+        let dynamic_multicall = dynamic_multicall.at_finalized().clear();
+        assert_eq!(dynamic_multicall.block(), Some(BlockId::finalized()));
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_multicaller_at_pending() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let provider = ProviderBuilder::new().connect_anvil_with_config(|a| a.fork(FORK_URL));
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let res = DynamicMulticallBuilder::new(provider.clone())
+            .add_call(DynCallItem::new(
+                WETH,
+                Vec::new(),
+                total_supply_function,
+                false,
+            ))
+            .at_pending()
+            .aggregate3()
+            .await
+            .unwrap();
+
+        assert_eq!(res.len(), 1);
+        assert!(res[0].is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_send() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let alice = address!("0000000000000000000000000000000000000a11");
+        let bob = address!("0000000000000000000000000000000000000b0b");
+        let provider = ProviderBuilder::new()
+            .connect_anvil_with_wallet_and_config(|a| a.fork(FORK_URL))
+            .unwrap();
+
+        let deposit_function = WETH::abi::functions()
+            .get("deposit")
+            .cloned()
+            .unwrap()
+            .first()
+            .unwrap()
+            .clone();
+
+        let transfer_function = erc20_function("transfer");
+
+        let balance_of_function = erc20_function("balanceOf");
+
+        let transfer = |to: Address| {
+            DynCallItem::new(
+                WETH,
+                vec![
+                    DynSolValue::Address(to),
+                    DynSolValue::Uint(U256::from(1), 256),
+                ],
+                transfer_function.clone(),
+                false,
+            )
+        };
+
+        // Wrap 2 wei into the multicall contract, then transfer it out in the same transaction.
+        let receipt = DynamicMulticallBuilder::new(provider.clone())
+            .add_call(
+                DynCallItem::new(WETH, Vec::new(), deposit_function, false).value(U256::from(2)),
+            )
+            .add_call(transfer(alice))
+            .add_call(transfer(bob))
+            .send()
+            .await
+            .unwrap();
+
+        assert!(receipt.status());
+
+        let res = DynamicMulticallBuilder::new(provider.clone())
+            .add_call(DynCallItem::new(
+                WETH,
+                vec![DynSolValue::Address(alice)],
+                balance_of_function.clone(),
+                false,
+            ))
+            .add_call(DynCallItem::new(
+                WETH,
+                vec![DynSolValue::Address(bob)],
+                balance_of_function,
+                false,
+            ))
+            .aggregate3()
+            .await
+            .unwrap();
+
+        for result in res {
+            assert_eq!(result.unwrap(), vec![DynSolValue::Uint(U256::from(1), 256)]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_multicaller_with_balance_slot_override() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let owner = address!("0000000000000000000000000000000000000a11");
+        let provider = ProviderBuilder::new().connect_anvil_with_config(|a| a.fork(FORK_URL));
+
+        let balance_of_function = erc20_function("balanceOf");
+
+        // WETH stores `balanceOf` in the mapping at slot 3.
+        let slot = keccak256(
+            DynSolValue::Tuple(vec![
+                DynSolValue::Address(owner),
+                DynSolValue::Uint(U256::from(3), 256),
+            ])
+            .abi_encode(),
+        );
+        let balance = U256::from(1_000_000);
+
+        let overrides = StateOverridesBuilder::default()
+            .with_state_diff(WETH, [(slot, B256::from(balance))])
+            .build();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider.clone())
+            .add_call(DynCallItem::new(
+                WETH,
+                vec![DynSolValue::Address(owner)],
+                balance_of_function,
+                false,
+            ))
+            .with_state_override(overrides.clone());
+
+        assert_eq!(dynamic_multicall.state_override(), Some(&overrides));
+
+        let res = dynamic_multicall.aggregate3().await.unwrap();
+
+        assert_eq!(
+            res[0].as_ref().unwrap(),
+            &vec![DynSolValue::Uint(balance, 256)]
+        );
+    }
+
+    #[test]
+    fn test_dyn_call_item_try_new() {
+        let balance_of_function = erc20_function("balanceOf");
+
+        assert!(DynCallItem::try_new(
+            WETH,
+            vec![DynSolValue::Address(WETH)],
+            balance_of_function.clone(),
+            false,
+        )
+        .is_ok());
+
+        let err =
+            DynCallItem::try_new(WETH, Vec::new(), balance_of_function.clone(), false).unwrap_err();
+        assert!(matches!(
+            err,
+            DynMulticallError::ParamCountMismatch {
+                expected: 1,
+                got: 0
+            }
+        ));
+
+        let err = DynCallItem::try_new(
+            WETH,
+            vec![DynSolValue::Uint(U256::from(1), 256)],
+            balance_of_function,
+            false,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            DynMulticallError::TypeMismatch { index: 0, expected, got }
+                if expected == "address" && got == "uint256"
+        ));
+    }
+
+    #[test]
+    fn test_state_override_helpers() {
+        let account = address!("0000000000000000000000000000000000000a11");
+        let (_, provider) = mock_provider();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .override_balance(account, U256::from(1))
+            .override_code(account, bytes!("602a60005260206000f3"))
+            .override_storage(account, B256::with_last_byte(1), B256::with_last_byte(2))
+            .override_state_diff(
+                account,
+                [(B256::with_last_byte(3), B256::with_last_byte(4))],
+            );
+
+        let account_override = &dynamic_multicall.state_override().unwrap()[&account];
+
+        assert_eq!(account_override.balance, Some(U256::from(1)));
+        assert_eq!(account_override.code, Some(bytes!("602a60005260206000f3")));
+
+        let state_diff = account_override.state_diff.as_ref().unwrap();
+        assert_eq!(state_diff.len(), 2);
+        assert_eq!(
+            state_diff[&B256::with_last_byte(1)],
+            B256::with_last_byte(2)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_multicaller_with_balance_override() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let account = address!("0000000000000000000000000000000000000a11");
+        let balance = U256::from(1_000_000);
+        let provider = ProviderBuilder::new().connect_anvil_with_config(|a| a.fork(FORK_URL));
+
+        let res = DynamicMulticallBuilder::new(provider.clone())
+            .add_get_eth_balance(account)
+            .override_balance(account, balance)
+            .aggregate3()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            res[0].as_ref().unwrap(),
+            &vec![DynSolValue::Uint(balance, 256)]
+        );
+    }
+
+    #[test]
+    fn test_dyn_call_item_from_signature() {
+        let recipient = address!("d8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
+
+        let transfer = DynCallItem::from_signature(
+            WETH,
+            "transfer(address,uint256)(bool)",
+            vec![
+                DynSolValue::Address(recipient),
+                DynSolValue::Uint(U256::from(1), 256),
+            ],
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            transfer.encode_input().unwrap(),
+            Bytes::from(
+                ERC20::transferCall {
+                    to: recipient,
+                    value: U256::from(1)
+                }
+                .abi_encode()
+            )
+        );
+
+        let err =
+            DynCallItem::from_signature(WETH, "transfer(address,", Vec::new(), false).unwrap_err();
+        assert!(matches!(err, DynMulticallError::SignatureParse { .. }));
+    }
+
+    #[test]
+    fn test_merge_state_override() {
+        let account = address!("0000000000000000000000000000000000000a11");
+        let other_account = address!("0000000000000000000000000000000000000b22");
+        let (_, provider) = mock_provider();
+
+        let mut dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .override_balance(account, U256::from(1))
+            .override_storage(account, B256::with_last_byte(1), B256::with_last_byte(1));
+
+        let overrides = StateOverridesBuilder::default()
+            .with_balance(account, U256::from(2))
+            .with_nonce(account, 7)
+            .with_state_diff(
+                account,
+                [(B256::with_last_byte(2), B256::with_last_byte(2))],
+            )
+            .with_balance(other_account, U256::from(3))
+            .build();
+
+        dynamic_multicall.merge_state_override(overrides).unwrap();
+
+        let state_override = dynamic_multicall.state_override().unwrap();
+        let account_override = &state_override[&account];
+
+        assert_eq!(account_override.balance, Some(U256::from(2)));
+        assert_eq!(account_override.nonce, Some(7));
+        assert_eq!(account_override.state_diff.as_ref().unwrap().len(), 2);
+        assert_eq!(state_override[&other_account].balance, Some(U256::from(3)));
+
+        let conflicting = StateOverridesBuilder::default()
+            .with_state(
+                account,
+                [(B256::with_last_byte(3), B256::with_last_byte(3))],
+            )
+            .build();
+
+        assert!(matches!(
+            dynamic_multicall.merge_state_override(conflicting),
+            Err(DynMulticallError::OverrideConflict { address }) if address == account
+        ));
+        assert!(dynamic_multicall.state_override().unwrap()[&account]
+            .state
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_raw() {
+        let (asserter, provider) = mock_provider();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let call_item = DynCallItem::new(WETH, Vec::new(), total_supply_function, true);
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_call(call_item.clone())
+            .add_call(call_item);
+
+        // Return data that doesn't match the `uint256` output is passed through as is.
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+            MulticallResult {
+                success: true,
+                returnData: bytes!("2a"),
+            },
+            MulticallResult {
+                success: false,
+                returnData: bytes!("deadbeef"),
+            },
+        ])));
+
+        let res = dynamic_multicall.aggregate3_raw().await.unwrap();
+
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0].as_ref().unwrap(), &bytes!("2a"));
+
+        let failure = res[1].as_ref().unwrap_err();
+        assert_eq!(failure.idx, 1);
+        assert_eq!(failure.return_data, bytes!("deadbeef"));
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_multicaller_with_custom_address() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let multicall = address!("0000000000000000000000000000000000001234");
+        let provider = ProviderBuilder::new().connect_anvil_with_config(|a| a.fork(FORK_URL));
+
+        // Deploy a copy of Multicall3 at the custom address.
+        let multicall_code = provider.get_code_at(MULTICALL3_ADDRESS).await.unwrap();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let balance_of_function = erc20_function("balanceOf");
+
+        let balance_of_call_item = DynCallItem::new(
+            WETH,
+            vec![DynSolValue::Address(address!(
+                "d8dA6BF26964aF9D7eEd9e03E53415D37aA96045"
+            ))],
+            balance_of_function,
+            false,
+        );
+
+        let total_supply_call_item =
+            DynCallItem::new(WETH, Vec::new(), total_supply_function, false);
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider.clone())
+            .with_address(multicall)
+            .override_code(multicall, multicall_code)
+            .clear()
+            .add_call(balance_of_call_item)
+            .add_call(total_supply_call_item);
+
+        assert_eq!(dynamic_multicall.address(), multicall);
+
+        let res = dynamic_multicall.aggregate3().await.unwrap();
+
+        assert_eq!(res.len(), 2);
+
+        for result in res {
+            let decoded = result.unwrap();
+            assert_eq!(decoded.len(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chain_multicall_address() {
+        let zksync_multicall = address!("F9cda624FBC7e059355ce98a31693d299FACd963");
+
+        assert_eq!(known_multicall_address(1), Some(MULTICALL3_ADDRESS));
+        assert_eq!(known_multicall_address(324), Some(zksync_multicall));
+        assert_eq!(known_multicall_address(u64::MAX), None);
+
+        let (asserter, provider) = mock_provider();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new_for_chain(provider.clone(), 324);
+        assert_eq!(dynamic_multicall.address(), zksync_multicall);
+
+        let dynamic_multicall = DynamicMulticallBuilder::new_for_chain(provider.clone(), u64::MAX);
+        assert_eq!(dynamic_multicall.address(), MULTICALL3_ADDRESS);
+
+        let mut dynamic_multicall = DynamicMulticallBuilder::new(provider);
+
+        asserter.push_success(&U64::from(324));
+        dynamic_multicall.resolve_address().await.unwrap();
+        assert_eq!(dynamic_multicall.address(), zksync_multicall);
+    }
+
+    #[test]
+    fn test_decode_as() {
+        let balance = U256::from(42);
+
+        let result = Ok(vec![DynSolValue::Uint(balance, 256)]);
+        assert_eq!(decode_as::<ERC20::balanceOfCall>(&result).unwrap(), balance);
+
+        let result = Ok(Vec::new());
+        assert!(matches!(
+            decode_as::<ERC20::balanceOfCall>(&result).unwrap_err(),
+            MulticallError::DecodeError(_)
+        ));
+
+        let result = Err(Failure {
+            idx: 0,
+            return_data: bytes!("deadbeef"),
+        });
+        assert!(matches!(
+            decode_as::<ERC20::balanceOfCall>(&result).unwrap_err(),
+            MulticallError::CallFailed(data) if data == bytes!("deadbeef")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_verify_deployment() {
+        let (asserter, provider) = mock_provider();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider);
+
+        asserter.push_success(&bytes!("6080"));
+        dynamic_multicall.verify_deployment().await.unwrap();
+
+        asserter.push_success(&Bytes::new());
+        asserter.push_success(&U64::from(31337));
+        let err = dynamic_multicall.verify_deployment().await.unwrap_err();
+
+        assert!(matches!(
+            err,
+            DynMulticallError::ContractNotDeployed { address, chain_id: 31337 }
+                if address == MULTICALL3_ADDRESS
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_multicaller_not_deployed() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let provider = ProviderBuilder::new().connect_anvil();
+
+        let err = DynamicMulticallBuilder::new(provider)
+            .verify_deployment()
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            DynMulticallError::ContractNotDeployed { address, .. }
+                if address == MULTICALL3_ADDRESS
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_clone_builder() {
+        let (asserter, provider) = mock_provider();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let base = DynamicMulticallBuilder::new(provider).add_call(DynCallItem::new(
+            WETH,
+            Vec::new(),
+            total_supply_function,
+            false,
+        ));
+
+        let at_block = base.clone().at_block_number(1);
+        let at_latest = base.clone().at_latest();
+
+        assert_eq!(base.block(), None);
+        assert_eq!(at_block.block(), Some(BlockId::number(1)));
+        assert_eq!(at_latest.block(), Some(BlockId::latest()));
+        assert_eq!(at_block.len(), base.len());
+
+        for dynamic_multicall in [base, at_block, at_latest] {
+            push_results(&asserter, [(true, 42)]);
+
+            let res = dynamic_multicall.aggregate3().await.unwrap();
+            assert_eq!(
+                res[0].as_ref().unwrap(),
+                &vec![DynSolValue::Uint(U256::from(42), 256)]
+            );
+        }
+    }
+
+    #[test]
+    fn test_iterate_calls() {
+        let (_, provider) = mock_provider();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_get_block_number()
+            .add_get_chain_id();
+
+        assert_eq!(dynamic_multicall.calls().len(), 2);
+        assert_eq!(dynamic_multicall[1].decoder.name, "getChainId");
+
+        let names = (&dynamic_multicall)
+            .into_iter()
+            .map(|c| c.decoder.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["getBlockNumber", "getChainId"]);
+    }
+
+    #[test]
+    fn test_injected_multicall_override() {
+        let multicall = address!("0000000000000000000000000000000000001234");
+        let (_, provider) = mock_provider();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider.clone());
+        assert!(dynamic_multicall.call_state_override().is_none());
+
+        let dynamic_multicall = dynamic_multicall
+            .with_address(multicall)
+            .override_balance(multicall, U256::from(1))
+            .with_injected_multicall();
+
+        let overrides = dynamic_multicall.call_state_override().unwrap();
+        let account_override = &overrides[&multicall];
+
+        // Runtime code of `MULTICALL3_ADDRESS` on mainnet.
+        let code = account_override.code.as_ref().unwrap();
+        assert_eq!(code.len(), 3808);
+        assert!(code.starts_with(&bytes!("6080604052600436106100f35760003560e01c")));
+        assert_eq!(account_override.balance, Some(U256::from(1)));
+
+        // User-provided code for the multicall address isn't replaced.
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .override_code(MULTICALL3_ADDRESS, bytes!("00"))
+            .with_injected_multicall();
+
+        assert_eq!(
+            dynamic_multicall.call_state_override().unwrap()[&MULTICALL3_ADDRESS].code,
+            Some(bytes!("00"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_multicaller_with_injected_multicall() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let provider = ProviderBuilder::new().connect_anvil();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_get_chain_id()
+            .add_get_eth_balance(Address::ZERO)
+            .with_injected_multicall();
+
+        dynamic_multicall.verify_deployment().await.unwrap_err();
+
+        let res = dynamic_multicall.aggregate3().await.unwrap();
+
+        assert_eq!(
+            res[0].as_ref().unwrap(),
+            &vec![DynSolValue::Uint(U256::from(31337), 256)]
+        );
+        assert!(res[1].is_ok());
+    }
+
+    #[test]
+    fn test_remove_and_insert_call() {
+        let (_, provider) = mock_provider();
+
+        let mut dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_get_block_number()
+            .add_get_chain_id();
+
+        let removed = dynamic_multicall.remove_call(0).unwrap();
+        assert_eq!(removed.decoder.name, "getBlockNumber");
+        assert!(dynamic_multicall.remove_call(1).is_none());
+
+        dynamic_multicall.insert_call(1, removed.clone());
+        dynamic_multicall.insert_call(0, removed);
+
+        let names = dynamic_multicall
+            .calls()
+            .iter()
+            .map(|c| c.decoder.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["getBlockNumber", "getChainId", "getBlockNumber"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn test_insert_call_out_of_bounds() {
+        let (_, provider) = mock_provider();
+
+        let mut dynamic_multicall = DynamicMulticallBuilder::new(provider).add_get_chain_id();
+        let call = dynamic_multicall.calls()[0].clone();
+
+        dynamic_multicall.insert_call(2, call);
+    }
+
+    #[test]
+    fn test_deployless_init_code() {
+        let calldata = bytes!("deadbeef");
+        let init_code = deployless_init_code(&calldata);
+
+        let helper_len = DEPLOYLESS_HELPER_LEN as usize;
+        assert_eq!(
+            init_code.len(),
+            helper_len + IMulticall3::BYTECODE.len() + calldata.len()
+        );
+        assert_eq!(
+            init_code[helper_len..helper_len + IMulticall3::BYTECODE.len()],
+            IMulticall3::BYTECODE[..]
+        );
+        assert!(init_code.ends_with(&calldata));
+
+        // The jump destination of the success branch.
+        assert_eq!(init_code[0x36], 0x5b);
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_deployless() {
+        let (asserter, provider) = mock_provider();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let call_item = DynCallItem::new(WETH, Vec::new(), total_supply_function, false);
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider).add_call(call_item.clone());
+
+        push_results(&asserter, [(true, 42)]);
+
+        let res = dynamic_multicall.aggregate3_deployless().await.unwrap();
+        assert_eq!(
+            res[0].as_ref().unwrap(),
+            &vec![DynSolValue::Uint(U256::from(42), 256)]
+        );
+
+        let err = dynamic_multicall
+            .add_call(call_item.value(U256::from(1)))
+            .aggregate3_deployless()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DynMulticallError::ValueInAggregate3 { indices } if indices == [1]));
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_multicaller_deployless() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let target = address!("0000000000000000000000000000000000000a11");
+        let provider = ProviderBuilder::new().connect_anvil();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        // Code returning 42 for any call.
+        let res = DynamicMulticallBuilder::new(provider)
+            .add_call(DynCallItem::new(
+                target,
+                Vec::new(),
+                total_supply_function,
+                false,
+            ))
+            .override_code(target, bytes!("602a60005260206000f3"))
+            .aggregate3_deployless()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            res[0].as_ref().unwrap(),
+            &vec![DynSolValue::Uint(U256::from(42), 256)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_labeled() {
+        let (asserter, provider) = mock_provider();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let call_item = DynCallItem::new(WETH, Vec::new(), total_supply_function, false);
+        let labeled_call_item = call_item.clone().label("weth_total_supply");
+
+        assert_eq!(
+            labeled_call_item.encode_input().unwrap(),
+            call_item.encode_input().unwrap()
+        );
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_call(labeled_call_item)
+            .add_call(call_item);
+
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(
+            &(1..=2)
+                .map(|supply| MulticallResult {
+                    success: true,
+                    returnData: U256::from(supply).to_be_bytes::<32>().into(),
+                })
+                .collect(),
+        )));
+
+        let res = dynamic_multicall.aggregate3_labeled().await.unwrap();
+
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0].0.as_deref(), Some("weth_total_supply"));
+        assert_eq!(
+            res[0].1.as_ref().unwrap(),
+            &vec![DynSolValue::Uint(U256::from(1), 256)]
+        );
+        assert_eq!(res[1].0, None);
+    }
+
+    #[tokio::test]
+    async fn test_execute_as_individual_calls() {
+        let (asserter, provider) = mock_provider();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let call_item = DynCallItem::new(WETH, Vec::new(), total_supply_function, true);
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_call(call_item.clone())
+            .add_call(call_item.clone());
+
+        let revert_data = bytes!("deadbeef");
+        let revert_payload = ErrorPayload::internal_error_with_message_and_obj(
+            "execution reverted".into(),
+            revert_data.clone(),
+        )
+        .serialize_payload()
+        .unwrap();
+
+        asserter.push_success(&Bytes::from(U256::from(42).to_be_bytes::<32>()));
+        asserter.push_failure(revert_payload.clone());
+
+        let res = dynamic_multicall
+            .execute_as_individual_calls()
+            .await
+            .unwrap();
+
+        assert_eq!(res.len(), 2);
+        assert_eq!(
+            res[0].as_ref().unwrap(),
+            &vec![DynSolValue::Uint(U256::from(42), 256)]
+        );
+
+        let failure = res[1].as_ref().unwrap_err();
+        assert_eq!(failure.idx, 1);
+        assert_eq!(failure.return_data, revert_data);
+
+        // Reverts without data fail with empty return data.
+        asserter.push_success(&Bytes::from(U256::from(42).to_be_bytes::<32>()));
+        asserter.push_failure(ErrorPayload {
+            code: -32000,
+            message: "execution reverted".into(),
+            data: None,
+        });
+
+        let res = dynamic_multicall
+            .execute_as_individual_calls()
+            .await
+            .unwrap();
+        assert_eq!(
+            res[1],
+            Err(Failure {
+                idx: 1,
+                return_data: Bytes::new(),
+            })
+        );
+
+        let dynamic_multicall = dynamic_multicall
+            .clear()
+            .add_call(call_item.allow_failure(false));
+
+        asserter.push_failure(revert_payload);
+
+        let err = dynamic_multicall
+            .execute_as_individual_calls()
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, DynMulticallError::Multicall(MulticallError::CallFailed(data)) if data == revert_data)
+        );
+
+        // Calls to the multicall contract get the injected multicall override.
+        let (recording_provider, requests) = connect_recording(&asserter);
+        let injected = DynamicMulticallBuilder::new(recording_provider)
+            .add_get_chain_id()
+            .add_get_block_number()
+            .with_injected_multicall();
+        asserter.push_success(&Bytes::from(U256::from(1).to_be_bytes::<32>()));
+        asserter.push_success(&Bytes::from(U256::from(2).to_be_bytes::<32>()));
+        injected.execute_as_individual_calls().await.unwrap();
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|(method, params)| {
+            method == "eth_call" && overrides_code(params, injected.address())
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_with_input_kind() {
+        let (asserter, provider) = mock_provider();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_call(DynCallItem::new(
+                WETH,
+                Vec::new(),
+                total_supply_function,
+                false,
+            ))
+            .with_input_kind(TransactionInputKind::Input);
+
+        let call = aggregate3Call { calls: Vec::new() };
+
+        let tx = dynamic_multicall.build_tx(&call, None);
+        assert!(tx.input.input.is_some() && tx.input.data.is_none());
+
+        let tx =
+            dynamic_multicall.build_tx_with_input_kind(&call, None, TransactionInputKind::Data);
+        assert!(tx.input.input.is_none() && tx.input.data.is_some());
+
+        push_results(&asserter, [(true, 42)]);
+
+        let res = dynamic_multicall
+            .aggregate3_with_input_kind(TransactionInputKind::Data)
+            .await
+            .unwrap();
+        assert_eq!(
+            res[0].as_ref().unwrap(),
+            &vec![DynSolValue::Uint(U256::from(42), 256)]
+        );
+        assert_eq!(dynamic_multicall.input_kind(), TransactionInputKind::Input);
+
+        // Failed calls are retried individually like in `aggregate3`.
+        let retrying = dynamic_multicall
+            .clone()
+            .clear()
+            .add_call(total_supply_call(true))
+            .retry_failures_individually(1);
+        push_results(&asserter, [(false, 0)]);
+        push_results(&asserter, [(true, 42)]);
+        let res = retrying
+            .aggregate3_with_input_kind(TransactionInputKind::Data)
+            .await
+            .unwrap();
+        assert_eq!(res[0], Ok(vec![DynSolValue::Uint(U256::from(42), 256)]));
+        assert!(asserter.read_q().is_empty());
+
+        let dynamic_multicall = dynamic_multicall.with_input_and_data();
+        assert_eq!(dynamic_multicall.input_kind(), TransactionInputKind::Both);
+
+        let tx = dynamic_multicall.build_tx(&call, None);
+        assert!(tx.input.input.is_some() && tx.input.input == tx.input.data);
+
+        let tx = dynamic_multicall
+            .with_input_kind(TransactionInputKind::Input)
+            .build_tx(&call, None);
+        assert!(tx.input.input.is_some() && tx.input.data.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_named() {
+        let (asserter, provider) = mock_provider();
+
+        let named = Function::parse("reserves()(uint112 reserve0, uint112 reserve1)").unwrap();
+        let unnamed = Function::parse("reserves()(uint112,uint112)").unwrap();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_call(DynCallItem::new(WETH, Vec::new(), named, false))
+            .add_call(DynCallItem::new(WETH, Vec::new(), unnamed, false));
+
+        let return_data: Bytes = DynSolValue::Tuple(vec![
+            DynSolValue::Uint(U256::from(1), 112),
+            DynSolValue::Uint(U256::from(2), 112),
+        ])
+        .abi_encode_params()
+        .into();
+
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+            MulticallResult {
+                success: true,
+                returnData: return_data.clone(),
+            },
+            MulticallResult {
+                success: true,
+                returnData: return_data,
+            },
+        ])));
+
+        let res = dynamic_multicall.aggregate3_named().await.unwrap();
+
+        let named = res[0].as_ref().unwrap();
+        assert_eq!(named["reserve0"], DynSolValue::Uint(U256::from(1), 112));
+        assert_eq!(named["reserve1"], DynSolValue::Uint(U256::from(2), 112));
+
+        let unnamed = res[1].as_ref().unwrap();
+        assert_eq!(
+            unnamed.keys().map(String::as_str).collect::<Vec<_>>(),
+            ["0", "1"]
+        );
+    }
+
+    #[test]
+    fn test_with_from() {
+        let from = address!("d8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
+        let (_, provider) = mock_provider();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider);
+        assert_eq!(dynamic_multicall.from(), None);
+
+        let tx = dynamic_multicall.build_tx(&aggregate3Call { calls: Vec::new() }, None);
+        assert_eq!(tx.from, None);
+
+        let dynamic_multicall = dynamic_multicall.with_from(from).clear();
+        assert_eq!(dynamic_multicall.from(), Some(from));
+
+        let tx = dynamic_multicall.build_tx(&aggregate3Call { calls: Vec::new() }, None);
+        assert_eq!(tx.from, Some(from));
+    }
+
+    #[tokio::test]
+    async fn test_try_block_and_aggregate_require_success() {
+        let multicall2 = address!("5BA1e12693Dc8F9c48aAD8770482f4739bEeD696");
+        let (asserter, provider) = mock_provider();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .with_address(multicall2)
+            .add_call(DynCallItem::new(
+                WETH,
+                Vec::new(),
+                total_supply_function,
+                true,
+            ));
+
+        let tx = dynamic_multicall.build_tx(
+            &tryBlockAndAggregateCall {
+                requireSuccess: true,
+                calls: dynamic_multicall.encode_calls().unwrap(),
+            },
+            None,
+        );
+        assert_eq!(TransactionBuilder::to(&tx), Some(multicall2));
+
+        // Multicall2 reverts with "Multicall2 aggregate: call failed".
+        let revert_data = bytes!("08c379a0deadbeef");
+
+        asserter.push_failure(
+            ErrorPayload::internal_error_with_message_and_obj(
+                "execution reverted".into(),
+                revert_data.clone(),
+            )
+            .serialize_payload()
+            .unwrap(),
+        );
+
+        let err = dynamic_multicall
+            .try_block_and_aggregate(true)
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(err, DynMulticallError::Multicall(MulticallError::CallFailed(data)) if data == revert_data)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_standalone_multicall_reads() {
+        let owner = address!("d8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
+        let (asserter, provider) = mock_provider();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider).at_block_number(20_000_000);
+
+        asserter.push_success(&Bytes::from(getEthBalanceCall::abi_encode_returns(
+            &U256::from(1),
+        )));
+        assert_eq!(
+            dynamic_multicall.get_eth_balance(owner).await.unwrap(),
+            U256::from(1)
+        );
+
+        asserter.push_success(&Bytes::from(getBlockNumberCall::abi_encode_returns(
+            &U256::from(20_000_000),
+        )));
+        assert_eq!(
+            dynamic_multicall.get_block_number().await.unwrap(),
+            20_000_000
+        );
+
+        asserter.push_success(&Bytes::from(
+            getCurrentBlockTimestampCall::abi_encode_returns(&U256::from(1_700_000_000)),
+        ));
+        assert_eq!(
+            dynamic_multicall
+                .get_current_block_timestamp()
+                .await
+                .unwrap(),
+            U256::from(1_700_000_000)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_call_gas_limit() {
+        let (asserter, provider) = mock_provider();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider).add_get_chain_id();
+        assert_eq!(dynamic_multicall.call_gas_limit(), None);
+
+        asserter.push_failure_msg("out of gas");
+        let err = dynamic_multicall.aggregate3().await.unwrap_err();
+        assert!(!err.to_string().contains("call gas limit"));
+
+        let dynamic_multicall = dynamic_multicall.with_call_gas_limit(25_000);
+        assert_eq!(dynamic_multicall.call_gas_limit(), Some(25_000));
+
+        asserter.push_failure_msg("out of gas");
+        let err = dynamic_multicall.aggregate3().await.unwrap_err();
+        assert!(err.to_string().contains("call gas limit set to 25000"));
+
+        // Other errors are left as is.
+        asserter.push_failure_msg("header not found");
+        let err = dynamic_multicall.aggregate3().await.unwrap_err();
+        assert!(!err.to_string().contains("call gas limit"));
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_multicaller_with_call_gas_limit() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let provider = ProviderBuilder::new().connect_anvil_with_config(|a| a.fork(FORK_URL));
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider).add_get_chain_id();
+
+        assert!(dynamic_multicall.aggregate3().await.is_ok());
+
+        let err = dynamic_multicall
+            .with_call_gas_limit(21_500)
+            .aggregate3()
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("call gas limit set to 21500"));
+    }
+
+    #[test]
+    fn test_with_calls() {
+        let (_, provider) = mock_provider();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let calls = [WETH, address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48")]
+            .into_iter()
+            .map(|token| DynCallItem::new(token, Vec::new(), total_supply_function.clone(), false))
+            .collect();
+
+        let dynamic_multicall = DynamicMulticallBuilder::with_calls(provider, calls);
+
+        assert_eq!(dynamic_multicall.len(), 2);
+        assert_eq!(dynamic_multicall.address(), MULTICALL3_ADDRESS);
+        assert_eq!(
+            dynamic_multicall[1].target,
+            address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fee_fields() {
+        let asserter = Asserter::new();
+        let (provider, requests) = connect_recording(&asserter);
+
+        let dynamic_multicall =
+            DynamicMulticallBuilder::new(provider).add_call(total_supply_call(false));
+
+        push_results(&asserter, [(true, 42)]);
+        dynamic_multicall.aggregate3().await.unwrap();
+
+        let request = requests.lock().unwrap().remove(0);
+        assert_eq!(request.0, "eth_call");
+        assert!(request.1[0].get("gas").is_none());
+        assert!(request.1[0].get("gasPrice").is_none());
+        assert!(request.1[0].get("maxFeePerGas").is_none());
+        assert!(request.1[0].get("maxPriorityFeePerGas").is_none());
+
+        let dynamic_multicall = dynamic_multicall
+            .with_call_gas_limit(1_000_000)
+            .with_gas_price(0)
+            .with_max_fee_per_gas(2_000_000_000)
+            .with_max_priority_fee_per_gas(1_000_000_000);
+
+        push_results(&asserter, [(true, 42)]);
+        dynamic_multicall.aggregate3().await.unwrap();
+
+        // The fields are part of the `eth_call` sent through the transport.
+        let request = requests.lock().unwrap().remove(0);
+        assert_eq!(request.0, "eth_call");
+        assert_eq!(request.1[0]["gas"], "0xf4240");
+        assert_eq!(request.1[0]["gasPrice"], "0x0");
+        assert_eq!(request.1[0]["maxFeePerGas"], "0x77359400");
+        assert_eq!(request.1[0]["maxPriorityFeePerGas"], "0x3b9aca00");
+    }
+
+    #[tokio::test]
+    async fn test_access_list() {
+        let (asserter, provider) = mock_provider();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider).add_get_chain_id();
+
+        let access_list = AccessList::from(vec![alloy::eips::eip2930::AccessListItem {
+            address: WETH,
+            storage_keys: vec![B256::with_last_byte(1)],
+        }]);
+
+        asserter.push_success(&AccessListResult {
+            access_list: access_list.clone(),
+            gas_used: U256::from(30_000),
+            error: None,
+        });
+
+        let result = dynamic_multicall.create_access_list().await.unwrap();
+        assert_eq!(result.access_list, access_list);
+        assert_eq!(result.gas_used, U256::from(30_000));
+
+        asserter.push_success(&AccessListResult {
+            access_list: AccessList::default(),
+            gas_used: U256::ZERO,
+            error: Some("execution reverted".to_string()),
+        });
+        assert!(dynamic_multicall.create_access_list().await.is_err());
+
+        // The injected multicall is part of the overrides.
+        let (recording_provider, requests) = connect_recording(&asserter);
+        let injected = DynamicMulticallBuilder::new(recording_provider)
+            .add_get_chain_id()
+            .with_injected_multicall();
+        asserter.push_success(&AccessListResult {
+            access_list: AccessList::default(),
+            gas_used: U256::ZERO,
+            error: None,
+        });
+        injected.create_access_list().await.unwrap();
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].0, "eth_createAccessList");
+        assert!(overrides_code(&requests[0].1, injected.address()));
+
+        let dynamic_multicall = dynamic_multicall.with_access_list(access_list.clone());
+        assert_eq!(dynamic_multicall.access_list(), Some(&access_list));
+
+        let tx = dynamic_multicall.build_tx(&aggregate3Call { calls: Vec::new() }, None);
+        assert_eq!(tx.access_list, Some(access_list));
+    }
+
+    #[tokio::test]
+    async fn test_simulate() {
+        let (asserter, provider) = mock_provider();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let call_item = DynCallItem::new(WETH, Vec::new(), total_supply_function, true);
+
+        let dynamic_multicall = DynamicMulticallBuilder::with_calls(provider, vec![call_item; 4]);
+
+        let revert: Bytes = Revert::from("insufficient balance").abi_encode().into();
+        let panic: Bytes = Panic::from(0x11).abi_encode().into();
+
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+            MulticallResult {
+                success: true,
+                returnData: U256::from(42).to_be_bytes::<32>().into(),
+            },
+            MulticallResult {
+                success: false,
+                returnData: revert,
+            },
+            MulticallResult {
+                success: false,
+                returnData: panic,
+            },
+            MulticallResult {
+                success: false,
+                returnData: bytes!("deadbeefcafe"),
+            },
+        ])));
+
+        let res = dynamic_multicall.simulate().await.unwrap();
+
+        assert!(res[0].result.is_ok());
+        assert_eq!(res[0].revert_reason, None);
+        assert_eq!(
+            res[1].revert_reason.as_deref(),
+            Some("insufficient balance")
+        );
+        assert_eq!(
+            res[2].revert_reason.as_deref(),
+            Some("panic 0x11: arithmetic underflow or overflow")
+        );
+        assert_eq!(
+            res[3].revert_reason.as_deref(),
+            Some("custom error 0xdeadbeef with 0xcafe")
+        );
+        assert_eq!(res[0].revert, None);
+        assert_eq!(
+            res[1].revert,
+            Some(RevertReason::Error("insufficient balance".to_string()))
+        );
+        assert_eq!(
+            res[3].result.as_ref().unwrap_err().return_data,
+            bytes!("deadbeefcafe")
+        );
+    }
+
+    #[test]
+    fn test_with_value() {
+        let (_, provider) = mock_provider();
+        let call = aggregate3Call { calls: Vec::new() };
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider);
+        assert_eq!(dynamic_multicall.value(), None);
+        assert_eq!(dynamic_multicall.build_tx(&call, None).value, None);
+        assert_eq!(
+            dynamic_multicall.build_tx(&call, Some(U256::from(1))).value,
+            Some(U256::from(1))
+        );
+
+        let dynamic_multicall = dynamic_multicall.with_value(U256::from(2)).clear();
+        assert_eq!(dynamic_multicall.value(), Some(U256::from(2)));
+        assert_eq!(
+            dynamic_multicall.build_tx(&call, None).value,
+            Some(U256::from(2))
+        );
+
+        // The builder value takes precedence over the sum of the call values.
+        assert_eq!(
+            dynamic_multicall.build_tx(&call, Some(U256::from(1))).value,
+            Some(U256::from(2))
+        );
+    }
+
+    #[test]
+    fn test_decode_revert_reason() {
+        assert_eq!(
+            decode_revert_reason(&Revert::from("not owner").abi_encode()).as_deref(),
+            Some("revert: not owner")
+        );
+        assert_eq!(
+            decode_revert_reason(&Panic::from(0x12).abi_encode()).as_deref(),
+            Some("panic: division or modulo by zero (0x12)")
+        );
+        assert_eq!(
+            decode_revert_reason(&bytes!("deadbeef")).as_deref(),
+            Some("custom error 0xdeadbeef")
+        );
+        assert_eq!(decode_revert_reason(&[]), None);
+    }
+
+    #[test]
+    fn test_build_transaction() {
+        let (_, provider) = mock_provider();
+
+        let deposit_function = WETH::abi::functions()
+            .get("deposit")
+            .cloned()
+            .unwrap()
+            .first()
+            .unwrap()
+            .clone();
+
+        let call_item = DynCallItem::new(WETH, Vec::new(), deposit_function, false);
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider).add_call(call_item.clone());
+
+        let tx = dynamic_multicall.build_transaction().unwrap();
+        assert_eq!(TransactionBuilder::to(&tx), Some(MULTICALL3_ADDRESS));
+        assert_eq!(tx.value, None);
+        assert_eq!(tx.input.input().unwrap()[..4], aggregate3Call::SELECTOR[..]);
+
+        let dynamic_multicall = dynamic_multicall
+            .clear()
+            .add_call(call_item.clone().value(U256::from(1)))
+            .add_call(call_item.value(U256::from(2)));
+
+        let tx = dynamic_multicall.build_transaction().unwrap();
+        assert_eq!(tx.value, Some(U256::from(3)));
+        assert_eq!(
+            tx.input.input().unwrap()[..4],
+            aggregate3ValueCall::SELECTOR[..]
+        );
+    }
+
+    #[test]
+    fn test_total_value() {
+        let (_, provider) = mock_provider();
+
+        let deposit_function = WETH::abi::functions()
+            .get("deposit")
+            .cloned()
+            .unwrap()
+            .first()
+            .unwrap()
+            .clone();
+
+        let call_item = DynCallItem::new(WETH, Vec::new(), deposit_function, false);
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider);
+        assert_eq!(dynamic_multicall.total_value().unwrap(), U256::ZERO);
+
+        let dynamic_multicall = dynamic_multicall
+            .add_call(call_item.clone().value(U256::MAX - U256::from(1)))
+            .add_call(call_item.clone().value(U256::from(1)));
+        assert_eq!(dynamic_multicall.total_value().unwrap(), U256::MAX);
+
+        let dynamic_multicall = dynamic_multicall.add_call(call_item.value(U256::from(1)));
+        assert!(matches!(
+            dynamic_multicall.total_value().unwrap_err(),
+            DynMulticallError::ValueOverflow
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_empty() {
+        // No responses are queued, so any request would fail.
+        let (_, provider) = mock_provider();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider);
+
+        assert!(dynamic_multicall.aggregate3().await.unwrap().is_empty());
+        assert!(dynamic_multicall.aggregate3_raw().await.unwrap().is_empty());
+        assert_eq!(
+            dynamic_multicall.aggregate3_chunked(10).await.unwrap(),
+            (None, Vec::new())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_raw_matches_aggregate3() {
+        let (asserter, provider) = mock_provider();
+
+        let total_supply_function = erc20_function("totalSupply");
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider).add_call(DynCallItem::new(
+            WETH,
+            Vec::new(),
+            total_supply_function.clone(),
+            false,
+        ));
+
+        let response = aggregate3_response([(true, 42)]);
+
+        asserter.push_success(&response);
+        let raw = dynamic_multicall.aggregate3_raw().await.unwrap();
+
+        asserter.push_success(&response);
+        let decoded = dynamic_multicall.aggregate3().await.unwrap();
+
+        assert_eq!(
+            &total_supply_function
+                .abi_decode_output(raw[0].as_ref().unwrap())
+                .unwrap(),
+            decoded[0].as_ref().unwrap()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_dyn_call_item_serde() {
+        let recipient = address!("d8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
+
+        let call_item = DynCallItem::from_signature(
+            WETH,
+            "transfer(address to, uint256 value)(bool)",
+            vec![
+                DynSolValue::Address(recipient),
+                DynSolValue::Uint(U256::from(1), 256),
+            ],
+            true,
+        )
+        .unwrap()
+        .value(U256::from(2))
+        .label("transfer");
+
+        let json = serde_json::to_value(&call_item).unwrap();
+        assert_eq!(json["target"], format!("{WETH:#x}"));
+        assert_eq!(json["function"]["name"], "transfer");
+
+        let deserialized: DynCallItem = serde_json::from_value(json).unwrap();
+
+        assert_eq!(deserialized.target, call_item.target);
+        assert_eq!(deserialized.params, call_item.params);
+        assert_eq!(deserialized.allow_failure, call_item.allow_failure);
+        assert_eq!(deserialized.value, call_item.value);
+        assert_eq!(deserialized.decoder, call_item.decoder);
+        assert_eq!(deserialized.label, call_item.label);
+        assert_eq!(
+            deserialized.encode_input().unwrap(),
+            call_item.encode_input().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_decode_aggregate3_response() {
+        let calls = vec![
+            total_supply_call(false),
+            DynCallItem::from_signature(WETH, "decimals()(uint8)", Vec::new(), true).unwrap(),
+        ];
+
+        let calldata = encode_aggregate3(&calls).unwrap();
+        let decoded_call = aggregate3Call::abi_decode(&calldata).unwrap();
+        assert_eq!(decoded_call.calls.len(), 2);
+        assert_eq!(decoded_call.calls[0].target, WETH);
+        assert_eq!(
+            decoded_call.calls[0].callData,
+            calls[0].encode_input().unwrap()
+        );
+        assert!(decoded_call.calls[1].allowFailure);
+
+        let response = Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+            MulticallResult {
+                success: true,
+                returnData: U256::from(42).to_be_bytes::<32>().into(),
+            },
+            MulticallResult {
+                success: false,
+                returnData: Bytes::new(),
+            },
+        ]));
+
+        let results = decode_aggregate3_response(&calls, &response).unwrap();
+        assert_eq!(
+            results[0].as_ref().unwrap(),
+            &vec![DynSolValue::Uint(U256::from(42), 256)]
+        );
+        assert_eq!(results[1].as_ref().unwrap_err().idx, 1);
+
+        assert!(decode_aggregate3_response(&calls, &[0xde, 0xad]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_with_retry() {
+        let (asserter, provider) = mock_provider();
+
+        let dynamic_multicall =
+            DynamicMulticallBuilder::new(provider).add_call(total_supply_call(false));
+
+        let response = aggregate3_response([(true, 42)]);
+
+        // Transient errors are retried until the call succeeds.
+        asserter.push_failure_msg("rate limited");
+        asserter.push_failure_msg("rate limited");
+        asserter.push_success(&response);
+        let result = dynamic_multicall
+            .aggregate3_with_retry(2, Duration::ZERO)
+            .await
+            .unwrap();
+        assert_eq!(
+            result[0].as_ref().unwrap(),
+            &vec![DynSolValue::Uint(U256::from(42), 256)]
+        );
+
+        // The error is returned once the retries are exhausted.
+        asserter.push_failure_msg("rate limited");
+        asserter.push_failure_msg("rate limited");
+        assert!(matches!(
+            dynamic_multicall
+                .aggregate3_with_retry(1, Duration::ZERO)
+                .await,
+            Err(DynMulticallError::Multicall(
+                MulticallError::TransportError(_)
+            ))
+        ));
+
+        // Decode errors fail fast, leaving the next response queued.
+        asserter.push_success(&Bytes::from_static(&[0xde, 0xad]));
+        asserter.push_success(&response);
+        assert!(matches!(
+            dynamic_multicall
+                .aggregate3_with_retry(1, Duration::ZERO)
+                .await,
+            Err(DynMulticallError::Multicall(MulticallError::DecodeError(_)))
+        ));
+        assert!(dynamic_multicall.aggregate3().await.is_ok());
+
+        // The retry policy replaces the one set on the builder rather than stacking on it.
+        let dynamic_multicall = dynamic_multicall.with_retry(1, Duration::ZERO);
+        for _ in 0..3 {
+            asserter.push_failure_msg("rate limited");
+        }
+        assert!(dynamic_multicall
+            .aggregate3_with_retry(1, Duration::ZERO)
+            .await
+            .is_err());
+        assert_eq!(asserter.read_q().len(), 1);
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let (_, provider) = mock_provider();
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider);
+        let base_delay = Duration::from_millis(100);
+
+        assert_eq!(dynamic_multicall.retry_delay(base_delay, 0), base_delay);
+        assert_eq!(dynamic_multicall.retry_delay(base_delay, 3), base_delay * 8);
+        assert_eq!(
+            dynamic_multicall.retry_delay(Duration::from_secs(1), 64),
+            Duration::from_secs(u32::MAX as u64)
+        );
+
+        let jittered = dynamic_multicall.clone().with_retry_jitter();
+        for attempt in 0..8 {
+            let delay = jittered.retry_delay(base_delay, attempt);
+            let backoff = base_delay * (1 << attempt);
+            assert!(delay >= backoff / 2 && delay <= backoff);
+        }
+
+        // A seeded jitter gives the same delays every time.
+        let seeded = dynamic_multicall.with_retry_jitter_seed(42);
+        let delays = (0..4)
+            .map(|attempt| seeded.retry_delay(base_delay, attempt))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            delays,
+            vec![
+                Duration::from_nanos(87_078_244),
+                Duration::from_nanos(115_991_039),
+                Duration::from_nanos(255_720_226),
+                Duration::from_nanos(537_676_287),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_request() {
+        let (_, provider) = mock_provider();
+
+        let call_item = total_supply_call(false);
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_call(call_item.clone())
+            .with_input_kind(TransactionInputKind::Both)
+            .with_call_gas_limit(1_000_000);
+
+        let calldata = dynamic_multicall.build_calldata().unwrap();
+        assert_eq!(calldata[..4], aggregate3Call::SELECTOR[..]);
+
+        let response = aggregate3Call::abi_encode_returns(&vec![MulticallResult {
+            success: true,
+            returnData: U256::from(42).to_be_bytes::<32>().into(),
+        }]);
+        let results = decode_aggregate3_response(dynamic_multicall.calls(), &response).unwrap();
+        assert_eq!(
+            results[0].as_ref().unwrap(),
+            &vec![DynSolValue::Uint(U256::from(42), 256)]
+        );
+
+        let tx = dynamic_multicall.build_request().unwrap();
+        assert_eq!(TransactionBuilder::to(&tx), Some(MULTICALL3_ADDRESS));
+        assert_eq!(tx.input.input, Some(calldata.clone()));
+        assert_eq!(tx.input.data, Some(calldata));
+        assert_eq!(tx.gas, Some(1_000_000));
+        assert_eq!(tx.value, None);
+
+        // Values are rejected like in aggregate3.
+        let dynamic_multicall = dynamic_multicall
+            .clear()
+            .add_call(call_item.value(U256::from(1)));
+        assert!(dynamic_multicall.build_calldata().is_err());
+        assert!(dynamic_multicall.build_request().is_err());
+    }
+
+    #[test]
+    fn test_calldata_size() {
+        let (_, provider) = mock_provider();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider);
+        assert_eq!(
+            dynamic_multicall.calldata_size().unwrap(),
+            dynamic_multicall.build_calldata().unwrap().len()
+        );
+
+        let dynamic_multicall = dynamic_multicall
+            .add_call(total_supply_call(false))
+            .add_call(
+                DynCallItem::from_signature(
+                    WETH,
+                    "balanceOf(address)(uint256)",
+                    vec![DynSolValue::Address(WETH)],
+                    true,
+                )
+                .unwrap(),
+            );
+
+        assert_eq!(
+            dynamic_multicall.calldata_size().unwrap(),
+            dynamic_multicall.build_calldata().unwrap().len()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_chunked_exact_boundary() {
+        let (asserter, dynamic_multicall) = mock_builder(4, true);
+        let dynamic_multicall = dynamic_multicall.without_block_pinning();
+
+        let success = |value: u64| MulticallResult {
+            success: true,
+            returnData: U256::from(value).to_be_bytes::<32>().into(),
+        };
+
+        // A chunk size equal to the call count makes a single request.
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(
+            &(0..4).map(success).collect::<Vec<_>>(),
+        )));
+        let (block, res) = dynamic_multicall.aggregate3_chunked(4).await.unwrap();
+        assert_eq!(block, None);
+        assert_eq!(res.len(), 4);
+        assert!(asserter.read_q().is_empty());
+
+        // Chunks ending exactly at the call count don't make an extra empty request.
+        for chunk in [[0, 1], [2, 3]] {
+            asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(
+                &chunk.map(success).to_vec(),
+            )));
+        }
+        let (_, res) = dynamic_multicall.aggregate3_chunked(2).await.unwrap();
+        for (idx, result) in res.iter().enumerate() {
+            assert_eq!(
+                result.as_ref().unwrap(),
+                &vec![DynSolValue::Uint(U256::from(idx), 256)]
+            );
+        }
+        assert!(asserter.read_q().is_empty());
+    }
+
+    #[test]
+    fn test_split_chunks_by_bytes() {
+        let call_with_data = |len: usize| {
+            DynCallItem::from_signature(
+                WETH,
+                "f(bytes)",
+                vec![DynSolValue::Bytes(vec![0xff; len])],
+                true,
+            )
+            .unwrap()
+        };
+
+        let calls = [10, 200, 40, 5_000, 1, 1, 1].map(call_with_data).to_vec();
+
+        let budget = 1_000;
+        let chunks = split_chunks(&calls, ChunkLimit::Bytes(budget)).unwrap();
+
+        let mut next_idx = 0;
+        for (offset, chunk) in &chunks {
+            assert_eq!(*offset, next_idx);
+            next_idx += chunk.len();
+
+            // The size estimate matches the actual calldata.
+            let size = encode_aggregate3(chunk).unwrap().len();
+            assert!(size <= budget || chunk.len() == 1);
+
+            // The chunk was closed because the next call didn't fit.
+            if let Some(next) = calls.get(next_idx) {
+                let mut extended = chunk.to_vec();
+                extended.push(next.clone());
+                assert!(encode_aggregate3(&extended).unwrap().len() > budget);
+            }
+        }
+        assert_eq!(next_idx, calls.len());
+
+        // The oversized call is sent on its own.
+        assert!(chunks
+            .iter()
+            .any(|(offset, chunk)| *offset == 3 && chunk.len() == 1));
+
+        assert!(matches!(
+            split_chunks(&calls, ChunkLimit::Bytes(0)),
+            Err(DynMulticallError::EmptyChunkSize)
+        ));
+        assert!(split_chunks(&[], ChunkLimit::Bytes(budget))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_chunked_by_bytes() {
+        let (asserter, dynamic_multicall) = mock_builder(3, true);
+        let dynamic_multicall = dynamic_multicall.without_block_pinning();
+
+        // Room for exactly two calls per chunk.
+        let budget = encode_aggregate3(&dynamic_multicall.calls()[..2])
             .unwrap()
+            .len();
+
+        let success = |value: u64| MulticallResult {
+            success: true,
+            returnData: U256::from(value).to_be_bytes::<32>().into(),
+        };
+
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+            success(0),
+            success(1),
+        ])));
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+            MulticallResult {
+                success: false,
+                returnData: Bytes::new(),
+            },
+        ])));
+
+        let (_, res) = dynamic_multicall
+            .aggregate3_chunked(ChunkLimit::Bytes(budget))
+            .await
+            .unwrap();
+
+        assert_eq!(res.len(), 3);
+        assert_eq!(
+            res[1].as_ref().unwrap(),
+            &vec![DynSolValue::Uint(U256::from(1), 256)]
+        );
+        assert_eq!(res[2].as_ref().unwrap_err().idx, 2);
+    }
+
+    #[tokio::test]
+    async fn test_with_provider() {
+        let (first_asserter, first_provider) = mock_provider();
+        let second_asserter = Asserter::new();
+        let second_provider = ProviderBuilder::new()
+            .connect_mocked_client(second_asserter.clone())
+            .root()
             .clone();
 
-        let balance_of_function = ERC20::abi::functions()
-            .get("balanceOf")
-            .cloned()
-            .unwrap()
-            .first()
+        let dynamic_multicall = DynamicMulticallBuilder::new(first_provider)
+            .add_call(total_supply_call(false))
+            .with_block(1u64)
+            .with_address(WETH);
+
+        let response = |value: u64| aggregate3_response([(true, value)]);
+
+        first_asserter.push_success(&response(1));
+        let first = dynamic_multicall.aggregate3().await.unwrap();
+
+        let dynamic_multicall = dynamic_multicall.with_provider(second_provider);
+        assert_eq!(dynamic_multicall.len(), 1);
+        assert_eq!(dynamic_multicall.block(), Some(BlockId::number(1)));
+        assert_eq!(dynamic_multicall.address(), WETH);
+
+        second_asserter.push_success(&response(2));
+        let second = dynamic_multicall.aggregate3().await.unwrap();
+
+        assert!(first_asserter.read_q().is_empty());
+        assert!(second_asserter.read_q().is_empty());
+        assert_ne!(first[0].as_ref().unwrap(), second[0].as_ref().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_concurrent_chunk_error() {
+        let (asserter, dynamic_multicall) = mock_builder(3, false);
+        let dynamic_multicall = dynamic_multicall.without_block_pinning();
+
+        let success = aggregate3_response([(true, 1)]);
+
+        asserter.push_success(&success);
+        asserter.push_failure_msg("rate limited");
+        asserter.push_success(&success);
+
+        assert!(matches!(
+            dynamic_multicall.aggregate3_concurrent(1, 3).await,
+            Err(DynMulticallError::Multicall(
+                MulticallError::TransportError(_)
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_zero_target() {
+        let (asserter, provider) = mock_provider();
+
+        let total_supply = |target, allow_failure| {
+            DynCallItem::from_signature(target, "totalSupply()(uint256)", Vec::new(), allow_failure)
+                .unwrap()
+        };
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_call(total_supply(WETH, false))
+            .add_call(total_supply(Address::ZERO, false));
+
+        // No responses are queued, so any request would fail.
+        assert!(matches!(
+            dynamic_multicall.aggregate3().await,
+            Err(DynMulticallError::ZeroTarget { idx: 1 })
+        ));
+        assert!(matches!(
+            dynamic_multicall.aggregate3_chunked(1).await,
+            Err(DynMulticallError::ZeroTarget { idx: 1 })
+        ));
+        assert!(matches!(
+            dynamic_multicall.aggregate3_value().await,
+            Err(DynMulticallError::ZeroTarget { idx: 1 })
+        ));
+
+        // Calls allowed to fail may target the zero address.
+        let dynamic_multicall = dynamic_multicall
+            .clear()
+            .add_call(total_supply(Address::ZERO, true));
+
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+            MulticallResult {
+                success: true,
+                returnData: Bytes::new(),
+            },
+        ])));
+        let res = dynamic_multicall.aggregate3().await.unwrap();
+        assert_eq!(res[0].as_ref().unwrap_err().idx, 0);
+    }
+
+    #[test]
+    fn test_reassemble_chunks_out_of_order() {
+        let calls = (0..50).collect::<Vec<usize>>();
+
+        // Xorshift, to shuffle deterministically.
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for chunk_size in 1..=calls.len() + 1 {
+            let sequential = calls
+                .chunks(chunk_size)
+                .enumerate()
+                .map(|(chunk_idx, chunk)| (chunk_idx * chunk_size, chunk.to_vec()))
+                .collect::<Vec<_>>();
+
+            for _ in 0..10 {
+                let mut shuffled = sequential.clone();
+                for i in (1..shuffled.len()).rev() {
+                    shuffled.swap(i, next() as usize % (i + 1));
+                }
+
+                assert_eq!(reassemble_chunks(calls.len(), shuffled).unwrap(), calls);
+            }
+
+            assert_eq!(
+                reassemble_chunks(calls.len(), sequential.clone()).unwrap(),
+                calls
+            );
+        }
+
+        // Missing, duplicated and out of range results are errors.
+        assert!(matches!(
+            reassemble_chunks(3, vec![(0, vec![0, 1])]),
+            Err(DynMulticallError::ChunkReassembly { idx: 2 })
+        ));
+        assert!(matches!(
+            reassemble_chunks(3, vec![(0, vec![0, 1]), (1, vec![1, 2])]),
+            Err(DynMulticallError::ChunkReassembly { idx: 1 })
+        ));
+        assert!(matches!(
+            reassemble_chunks(3, vec![(0, vec![0, 1]), (2, vec![2, 3])]),
+            Err(DynMulticallError::ChunkReassembly { idx: 3 })
+        ));
+        assert!(reassemble_chunks::<usize>(0, Vec::new())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_strict_decode() {
+        let (asserter, provider) = mock_provider();
+
+        let dynamic_multicall =
+            DynamicMulticallBuilder::new(provider).add_call(total_supply_call(true));
+
+        // Two words returned for a single uint256 output.
+        let response = Bytes::from(aggregate3Call::abi_encode_returns(&vec![MulticallResult {
+            success: true,
+            returnData: DynSolValue::Tuple(vec![
+                DynSolValue::Uint(U256::from(1), 256),
+                DynSolValue::Uint(U256::from(2), 256),
+            ])
+            .abi_encode_params()
+            .into(),
+        }]));
+
+        asserter.push_success(&response);
+        let res = dynamic_multicall.aggregate3().await.unwrap();
+        assert_eq!(
+            res[0].as_ref().unwrap(),
+            &vec![DynSolValue::Uint(U256::from(1), 256)]
+        );
+
+        let dynamic_multicall = dynamic_multicall.with_strict_decode();
+
+        asserter.push_success(&response);
+        let err = dynamic_multicall.aggregate3().await.unwrap_err();
+        let DynMulticallError::OutputLengthMismatch {
+            idx: 0,
+            target,
+            signature,
+            ..
+        } = &err
+        else {
+            panic!("expected an output length mismatch");
+        };
+        assert_eq!(*target, WETH);
+        assert_eq!(signature, "function totalSupply() returns (uint256)");
+        assert!(err.to_string().contains(&WETH.to_string()));
+
+        push_results(&asserter, [(true, 1)]);
+        assert!(dynamic_multicall.aggregate3().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_chunked_block_pinning() {
+        let (asserter, dynamic_multicall) = mock_builder(2, true);
+
+        let response = aggregate3_response([(true, 1)]);
+
+        // The current block number is fetched once for all chunks.
+        asserter.push_success(&U64::from(20_000_000));
+        asserter.push_success(&response);
+        asserter.push_success(&response);
+        let (block, res) = dynamic_multicall.aggregate3_chunked(1).await.unwrap();
+        assert_eq!(block, Some(20_000_000));
+        assert_eq!(res.len(), 2);
+        assert!(asserter.read_q().is_empty());
+
+        // An explicit block is used as is.
+        let pinned = dynamic_multicall.clone().with_block(1u64);
+        asserter.push_success(&response);
+        asserter.push_success(&response);
+        let (block, _) = pinned.aggregate3_concurrent(1, 2).await.unwrap();
+        assert_eq!(block, None);
+        assert!(asserter.read_q().is_empty());
+
+        // Pinning can be disabled.
+        let unpinned = dynamic_multicall.without_block_pinning();
+        asserter.push_success(&response);
+        asserter.push_success(&response);
+        let (block, _) = unpinned.aggregate3_chunked(1).await.unwrap();
+        assert_eq!(block, None);
+        assert!(asserter.read_q().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_chunked_consistency_check() {
+        let (asserter, dynamic_multicall) = mock_builder(2, true);
+        let dynamic_multicall = dynamic_multicall.with_consistency_check(1);
+
+        let chunk_response = |parent_hash: B256, value: u64| {
+            Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+                MulticallResult {
+                    success: true,
+                    returnData: parent_hash.into(),
+                },
+                MulticallResult {
+                    success: true,
+                    returnData: U256::from(1_700_000_000).to_be_bytes::<32>().into(),
+                },
+                MulticallResult {
+                    success: true,
+                    returnData: U256::from(value).to_be_bytes::<32>().into(),
+                },
+            ]))
+        };
+
+        let (first, second) = (B256::repeat_byte(1), B256::repeat_byte(2));
+
+        // The second chunk sees a different block, so the run is retried from a new block.
+        asserter.push_success(&U64::from(10));
+        asserter.push_success(&chunk_response(first, 0));
+        asserter.push_success(&chunk_response(second, 1));
+        asserter.push_success(&U64::from(11));
+        asserter.push_success(&chunk_response(second, 0));
+        asserter.push_success(&chunk_response(second, 1));
+
+        let (block, res) = dynamic_multicall.aggregate3_chunked(1).await.unwrap();
+        assert_eq!(block, Some(11));
+        for (idx, result) in res.iter().enumerate() {
+            assert_eq!(
+                result.as_ref().unwrap(),
+                &vec![DynSolValue::Uint(U256::from(idx), 256)]
+            );
+        }
+        assert!(asserter.read_q().is_empty());
+
+        // The error is returned once the retries are exhausted.
+        for block in [12, 13] {
+            asserter.push_success(&U64::from(block));
+            asserter.push_success(&chunk_response(first, 0));
+            asserter.push_success(&chunk_response(second, 1));
+        }
+
+        let err = dynamic_multicall.aggregate3_chunked(1).await.unwrap_err();
+        assert!(matches!(
+            err,
+            DynMulticallError::Consistency { block: 13, parent_hashes, .. }
+                if parent_hashes == vec![first, second]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_simulate_bundle() {
+        let account = address!("0000000000000000000000000000000000000a11");
+        let (asserter, provider) = mock_provider();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_call(
+                DynCallItem::from_signature(
+                    WETH,
+                    "balanceOf(address)(uint256)",
+                    vec![DynSolValue::Address(account)],
+                    false,
+                )
+                .unwrap(),
+            )
+            .override_storage(WETH, B256::with_last_byte(1), B256::with_last_byte(1));
+
+        let steps = [1u64, 2].map(|balance| {
+            StateOverridesBuilder::default()
+                .with_balance(account, U256::from(balance))
+                .build()
+        });
+
+        for balance in [1u64, 2] {
+            push_results(&asserter, [(true, balance)]);
+        }
+
+        let results = dynamic_multicall
+            .simulate_bundle(steps.to_vec())
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 2);
+        for (step, balance) in results.iter().zip([1u64, 2]) {
+            assert_eq!(
+                step[0].as_ref().unwrap(),
+                &vec![DynSolValue::Uint(U256::from(balance), 256)]
+            );
+        }
+
+        // A step conflicting with the builder's overrides fails before any request.
+        let conflicting = StateOverridesBuilder::default()
+            .with_state_diff(WETH, [(B256::with_last_byte(2), B256::with_last_byte(2))])
+            .with_state(WETH, [(B256::with_last_byte(3), B256::with_last_byte(3))])
+            .build();
+        assert!(matches!(
+            dynamic_multicall.simulate_bundle(vec![conflicting]).await,
+            Err(DynMulticallError::OverrideConflict { address }) if address == WETH
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_stream() {
+        let (asserter, dynamic_multicall) = mock_builder(3, true);
+
+        let success = |value: u64| MulticallResult {
+            success: true,
+            returnData: U256::from(value).to_be_bytes::<32>().into(),
+        };
+
+        asserter.push_success(&U64::from(1));
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+            success(0),
+            success(1),
+        ])));
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+            success(2),
+        ])));
+
+        let chunks = dynamic_multicall
+            .aggregate3_stream(2)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].range, 0..2);
+        assert_eq!(chunks[1].range, 2..3);
+        assert!(chunks.iter().all(|chunk| chunk.block == Some(1)));
+        for chunk in &chunks {
+            for (idx, result) in chunk.range.clone().zip(&chunk.results) {
+                assert_eq!(
+                    result.as_ref().unwrap(),
+                    &vec![DynSolValue::Uint(U256::from(idx), 256)]
+                );
+            }
+        }
+
+        // Chunks are only executed when polled.
+        asserter.push_success(&U64::from(1));
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+            success(0),
+            success(1),
+        ])));
+        {
+            let mut stream = std::pin::pin!(dynamic_multicall.aggregate3_stream(2));
+            assert_eq!(stream.next().await.unwrap().unwrap().range, 0..2);
+        }
+        assert!(asserter.read_q().is_empty());
+
+        // Validation errors are the first item.
+        let mut stream = std::pin::pin!(dynamic_multicall.aggregate3_stream(0));
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(DynMulticallError::EmptyChunkSize))
+        ));
+        assert!(stream.next().await.is_none());
+    }
+
+    #[test]
+    fn test_calls_by_target() {
+        let dai = address!("6B175474E89094C44Da98b954EedeAC495271d0F");
+        let (_, provider) = mock_provider();
+
+        let total_supply = |target| {
+            DynCallItem::from_signature(target, "totalSupply()(uint256)", Vec::new(), false)
+                .unwrap()
+        };
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider);
+        assert!(dynamic_multicall.calls_by_target().is_empty());
+
+        let dynamic_multicall = dynamic_multicall
+            .add_call(total_supply(WETH))
+            .add_call(total_supply(dai))
+            .add_call(total_supply(WETH));
+
+        let counts = dynamic_multicall.calls_by_target();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&WETH], 2);
+        assert_eq!(counts[&dai], 1);
+    }
+
+    #[tokio::test]
+    async fn test_with_progress() {
+        let (asserter, provider) = mock_provider();
+
+        let call_item = total_supply_call(true);
+
+        let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_calls(std::iter::repeat_n(call_item, 3))
+            .without_block_pinning()
+            .with_progress({
+                let reports = reports.clone();
+                move |progress| reports.lock().unwrap().push(progress)
+            });
+
+        let response = |len: usize| {
+            Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+                MulticallResult {
+                    success: true,
+                    returnData: U256::from(1).to_be_bytes::<32>().into(),
+                };
+                len
+            ]))
+        };
+
+        asserter.push_success(&response(2));
+        asserter.push_success(&response(1));
+        dynamic_multicall.aggregate3_chunked(2).await.unwrap();
+
+        {
+            let reports = reports.lock().unwrap();
+            assert_eq!(reports.len(), 2);
+            assert_eq!(
+                (reports[0].chunks_completed, reports[0].calls_completed),
+                (1, 2)
+            );
+            assert_eq!(
+                (reports[1].chunks_completed, reports[1].calls_completed),
+                (2, 3)
+            );
+            assert!(reports
+                .iter()
+                .all(|p| p.chunks_total == 2 && p.calls_total == 3));
+        }
+
+        // Failed chunks are reported too.
+        asserter.push_success(&response(2));
+        asserter.push_failure_msg("rate limited");
+        assert!(dynamic_multicall.aggregate3_concurrent(2, 2).await.is_err());
+        assert_eq!(reports.lock().unwrap().len(), 4);
+
+        // A panicking callback doesn't abort the execution.
+        let dynamic_multicall = dynamic_multicall.with_progress(|_| panic!("progress"));
+        asserter.push_success(&response(2));
+        asserter.push_success(&response(1));
+        assert_eq!(
+            dynamic_multicall
+                .aggregate3_chunked(2)
+                .await
+                .unwrap()
+                .1
+                .len(),
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_adaptive() {
+        let (asserter, dynamic_multicall) = mock_builder(8, true);
+
+        let result = |value: u64| MulticallResult {
+            success: value != 2,
+            returnData: U256::from(value).to_be_bytes::<32>().into(),
+        };
+
+        // The first chunk exceeds the gas cap and is split, then the size grows back by a quarter.
+        asserter.push_success(&U64::from(100));
+        asserter.push_failure_msg("out of gas");
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(
+            &(0..4).map(result).collect::<Vec<_>>(),
+        )));
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(
+            &(4..8).map(result).collect::<Vec<_>>(),
+        )));
+
+        let (stats, res) = dynamic_multicall.aggregate3_adaptive(8, 1).await.unwrap();
+        assert_eq!(
+            stats,
+            AdaptiveStats {
+                block: Some(100),
+                final_chunk_size: 5,
+                splits: 1,
+                requests: 3,
+            }
+        );
+        assert_eq!(res.len(), 8);
+        for (idx, result) in res.iter().enumerate() {
+            match result {
+                Ok(values) => assert_eq!(values, &vec![DynSolValue::Uint(U256::from(idx), 256)]),
+                Err(failure) => assert_eq!((idx, failure.idx), (2, 2)),
+            }
+        }
+        assert!(asserter.read_q().is_empty());
+
+        // A revert without any data is treated as exceeding the gas cap, whether the node sends
+        // empty data or none, and the progress of each successful chunk is reported.
+        let empty_revert = ErrorPayload::internal_error_with_message_and_obj(
+            "execution reverted".into(),
+            Bytes::new(),
+        )
+        .serialize_payload()
+        .unwrap();
+
+        let progress = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let dynamic_multicall = dynamic_multicall
+            .with_block(BlockId::number(100))
+            .with_progress({
+                let progress = progress.clone();
+                move |p| {
+                    progress
+                        .lock()
+                        .unwrap()
+                        .push((p.chunks_completed, p.calls_completed))
+                }
+            });
+        asserter.push_failure(empty_revert);
+        for chunk in [0..4, 4..8] {
+            asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(
+                &chunk.map(result).collect::<Vec<_>>(),
+            )));
+        }
+        let (stats, _) = dynamic_multicall.aggregate3_adaptive(8, 1).await.unwrap();
+        assert_eq!((stats.block, stats.splits, stats.requests), (None, 1, 3));
+        assert_eq!(*progress.lock().unwrap(), vec![(1, 4), (2, 8)]);
+        assert!(asserter.read_q().is_empty());
+
+        asserter.push_failure(ErrorPayload {
+            code: -32000,
+            message: "execution reverted".into(),
+            data: None,
+        });
+        for chunk in [0..4, 4..8] {
+            asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(
+                &chunk.map(result).collect::<Vec<_>>(),
+            )));
+        }
+        let (stats, _) = dynamic_multicall.aggregate3_adaptive(8, 1).await.unwrap();
+        assert_eq!((stats.splits, stats.requests), (1, 3));
+        assert!(asserter.read_q().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_adaptive_errors() {
+        let (asserter, dynamic_multicall) = mock_builder(2, true);
+        let dynamic_multicall = dynamic_multicall.without_block_pinning();
+
+        assert!(matches!(
+            dynamic_multicall.aggregate3_adaptive(0, 1).await,
+            Err(DynMulticallError::EmptyChunkSize)
+        ));
+        assert!(matches!(
+            dynamic_multicall.aggregate3_adaptive(2, 0).await,
+            Err(DynMulticallError::EmptyChunkSize)
+        ));
+
+        // A single call exceeding the gas cap returns the error.
+        asserter.push_failure_msg("out of gas");
+        asserter.push_failure_msg("out of gas");
+        let err = dynamic_multicall
+            .aggregate3_adaptive(2, 1)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("out of gas"));
+        assert!(asserter.read_q().is_empty());
+
+        // Chunks aren't split below the minimum size.
+        asserter.push_failure_msg("out of gas");
+        assert!(dynamic_multicall.aggregate3_adaptive(2, 2).await.is_err());
+        assert!(asserter.read_q().is_empty());
+
+        // Chunks are split down to the minimum size, not below it.
+        let dynamic_multicall =
+            dynamic_multicall.add_calls(std::iter::repeat_n(total_supply_call(true), 2));
+        let results = |len| {
+            Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+                MulticallResult {
+                    success: true,
+                    returnData: U256::from(42).to_be_bytes::<32>().into(),
+                };
+                len
+            ]))
+        };
+        asserter.push_failure_msg("out of gas");
+        asserter.push_success(&results(3));
+        asserter.push_success(&results(1));
+        let (stats, res) = dynamic_multicall.aggregate3_adaptive(4, 3).await.unwrap();
+        assert_eq!((stats.splits, stats.requests), (1, 3));
+        assert!(res.iter().all(Result::is_ok));
+        assert!(asserter.read_q().is_empty());
+
+        // Other errors aren't retried.
+        asserter.push_failure_msg("header not found");
+        let err = dynamic_multicall
+            .aggregate3_adaptive(2, 1)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("header not found"));
+        assert!(asserter.read_q().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_indexed() {
+        let (asserter, dynamic_multicall) = mock_builder(3, true);
+
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(
+            &(0..3)
+                .map(|value: u64| MulticallResult {
+                    success: value != 1,
+                    returnData: U256::from(value).to_be_bytes::<32>().into(),
+                })
+                .collect::<Vec<_>>(),
+        )));
+
+        // Indices survive reordering the results.
+        let mut res = dynamic_multicall.aggregate3_indexed().await.unwrap();
+        res.reverse();
+
+        for (idx, result) in res {
+            match result {
+                Ok(values) => assert_eq!(values, vec![DynSolValue::Uint(U256::from(idx), 256)]),
+                Err(failure) => assert_eq!((idx, failure.idx), (1, 1)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_encoded_calls() {
+        let (_, provider) = mock_provider();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_call(total_supply_call(false))
+            .add_call(
+                DynCallItem::from_signature(
+                    Address::ZERO,
+                    "balanceOf(address)(uint256)",
+                    vec![DynSolValue::Address(WETH)],
+                    true,
+                )
+                .unwrap(),
+            );
+
+        let mut balance_of = bytes!("70a08231").to_vec();
+        balance_of.extend_from_slice(WETH.into_word().as_slice());
+
+        assert_eq!(
+            dynamic_multicall.encoded_calls().unwrap(),
+            vec![
+                (WETH, bytes!("18160ddd")),
+                (Address::ZERO, Bytes::from(balance_of)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_with_retry() {
+        let asserter = Asserter::new();
+        let provider = connect_flaky(&asserter);
+
+        let call_item = total_supply_call(false);
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_calls(std::iter::repeat_n(call_item, 2))
+            .without_block_pinning()
+            .with_retry(1, Duration::ZERO);
+
+        let response = aggregate3_response([(true, 42)]);
+
+        // Each chunk is retried independently.
+        for _ in 0..2 {
+            push_transport_failure(&asserter, "connection reset");
+            asserter.push_success(&response);
+        }
+        let (_, res) = dynamic_multicall.aggregate3_chunked(1).await.unwrap();
+        assert!(res.iter().all(Result::is_ok));
+        assert!(asserter.read_q().is_empty());
+
+        // The error is returned once the retries are exhausted.
+        push_transport_failure(&asserter, "connection reset");
+        push_transport_failure(&asserter, "connection reset");
+        assert!(matches!(
+            dynamic_multicall.get_block_number().await,
+            Err(DynMulticallError::Multicall(
+                MulticallError::TransportError(_)
+            ))
+        ));
+        assert!(asserter.read_q().is_empty());
+
+        // Reverts, with or without data, and other error responses aren't retried.
+        let revert_payload = ErrorPayload::internal_error_with_message_and_obj(
+            "execution reverted".into(),
+            bytes!("deadbeef"),
+        )
+        .serialize_payload()
+        .unwrap();
+        let empty_revert_payload = ErrorPayload {
+            code: -32000,
+            message: "execution reverted".into(),
+            data: None,
+        };
+        let invalid_params_payload = ErrorPayload {
+            code: -32602,
+            message: "invalid params".into(),
+            data: None,
+        };
+        for payload in [revert_payload, empty_revert_payload, invalid_params_payload] {
+            asserter.push_failure(payload);
+            asserter.push_success(&U256::from(1).to_be_bytes::<32>());
+            assert!(dynamic_multicall
+                .get_current_block_timestamp()
+                .await
+                .is_err());
+            assert_eq!(
+                dynamic_multicall
+                    .get_current_block_timestamp()
+                    .await
+                    .unwrap(),
+                U256::from(1)
+            );
+        }
+    }
+
+    #[test]
+    fn test_dedup() {
+        let (_, provider) = mock_provider();
+
+        let balance_of = |owner| {
+            DynCallItem::from_signature(
+                WETH,
+                "balanceOf(address)(uint256)",
+                vec![DynSolValue::Address(owner)],
+                true,
+            )
             .unwrap()
-            .clone();
+        };
 
-        let balance_of_call_item = DynCallItem::new(
-            weth,
-            vec![DynSolValue::Address(address!(
-                "d8dA6BF26964aF9D7eEd9e03E53415D37aA96045"
-            ))],
-            balance_of_function,
-            false,
+        let mut dynamic_multicall = DynamicMulticallBuilder::new(provider).add_calls([
+            total_supply_call(true),
+            balance_of(Address::ZERO),
+            total_supply_call(false),
+            balance_of(WETH),
+            balance_of(Address::ZERO),
+            total_supply_call(true).value(U256::from(1)),
+        ]);
+
+        let mapping = dynamic_multicall.dedup().unwrap();
+        assert_eq!(mapping, vec![0, 1, 0, 2, 1, 3]);
+        assert_eq!(dynamic_multicall.len(), 4);
+
+        // The kept call can only fail if all of its duplicates can.
+        let allow_failures = dynamic_multicall
+            .calls()
+            .iter()
+            .map(|c| c.allow_failure)
+            .collect::<Vec<_>>();
+        assert_eq!(allow_failures, vec![false, true, true, true]);
+
+        let results = vec![
+            Ok(vec![DynSolValue::Uint(U256::from(100), 256)]),
+            Err(Failure {
+                idx: 1,
+                return_data: Bytes::new(),
+            }),
+            Ok(vec![DynSolValue::Uint(U256::from(1), 256)]),
+            Ok(vec![DynSolValue::Uint(U256::from(100), 256)]),
+        ];
+        let fanned_out = fan_out_results(&results, &mapping);
+        assert_eq!(fanned_out.len(), 6);
+        assert_eq!(fanned_out[2], results[0]);
+        assert_eq!(fanned_out[3], results[2]);
+        assert_eq!(fanned_out[4].as_ref().unwrap_err().idx, 4);
+
+        // Deduplicating again is a no-op.
+        assert_eq!(dynamic_multicall.dedup().unwrap(), vec![0, 1, 2, 3]);
+
+        // The same calldata decoded with other outputs isn't a duplicate.
+        dynamic_multicall.insert_call(
+            4,
+            DynCallItem::from_signature(WETH, "totalSupply()(int256)", Vec::new(), true).unwrap(),
         );
+        assert_eq!(dynamic_multicall.dedup().unwrap(), vec![0, 1, 2, 3, 4]);
+    }
 
-        let total_supply_call_item =
-            DynCallItem::new(weth, Vec::new(), total_supply_function, false);
+    #[tokio::test(start_paused = true)]
+    async fn test_with_rate_limit_retry() {
+        let (asserter, provider) = mock_provider();
 
-        let mut dynamic_multicall = DynamicMulticallBuilder::new(provider.clone());
+        let dynamic_multicall =
+            DynamicMulticallBuilder::new(provider).add_call(total_supply_call(false));
 
-        for _ in 0..20 {
-            dynamic_multicall = dynamic_multicall
-                .add_call(balance_of_call_item.clone())
-                .add_call(total_supply_call_item.clone());
+        let response = aggregate3_response([(true, 42)]);
+        let rate_limited = || {
+            serde_json::from_str::<ErrorPayload>(r#"{"code":429,"message":"Too Many Requests"}"#)
+                .unwrap()
+        };
+
+        // Rate limits fail fast unless enabled.
+        asserter.push_failure(rate_limited());
+        assert!(dynamic_multicall.aggregate3().await.is_err());
+
+        let dynamic_multicall = dynamic_multicall.with_rate_limit_retry(usize::MAX);
+
+        // The delay doubles from the base delay.
+        asserter.push_failure(rate_limited());
+        asserter.push_failure(rate_limited());
+        asserter.push_success(&response);
+        let start = tokio::time::Instant::now();
+        let result = dynamic_multicall.aggregate3().await.unwrap();
+        assert_eq!(
+            result[0].as_ref().unwrap(),
+            &vec![DynSolValue::Uint(U256::from(42), 256)]
+        );
+        assert_eq!(start.elapsed(), RATE_LIMIT_BASE_DELAY * 3);
+        assert!(asserter.read_q().is_empty());
+
+        // The backoff requested by the RPC is honored.
+        asserter.push_failure(
+            serde_json::from_str::<ErrorPayload>(
+                r#"{"code":-32005,"message":"project ID request rate exceeded","data":{"rate":{"backoff_seconds":5}}}"#,
+            )
+            .unwrap(),
+        );
+        asserter.push_success(&response);
+        let start = tokio::time::Instant::now();
+        assert!(dynamic_multicall.aggregate3().await.is_ok());
+        assert_eq!(start.elapsed(), Duration::from_secs(5));
+
+        // Other errors aren't retried by the rate limit policy.
+        asserter.push_failure_msg("connection reset");
+        asserter.push_success(&response);
+        assert!(dynamic_multicall.aggregate3().await.is_err());
+        assert!(dynamic_multicall.aggregate3().await.is_ok());
+
+        // The error is returned once the retries are exhausted.
+        let dynamic_multicall = dynamic_multicall.with_rate_limit_retry(1);
+        asserter.push_failure(rate_limited());
+        asserter.push_failure(rate_limited());
+        assert!(dynamic_multicall.aggregate3().await.is_err());
+        assert!(asserter.read_q().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_with_timeout() {
+        let asserter = Asserter::new();
+        let provider = connect_flaky(&asserter);
+
+        let call_item = total_supply_call(false);
+
+        let timeout = Duration::from_secs(10);
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_calls(std::iter::repeat_n(call_item, 2))
+            .without_block_pinning()
+            .with_retry(usize::MAX, Duration::from_secs(3))
+            .with_timeout(timeout);
+
+        let response = Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+            MulticallResult {
+                success: true,
+                returnData: U256::from(42).to_be_bytes::<32>().into(),
+            };
+            2
+        ]));
+
+        asserter.push_success(&response);
+        assert!(dynamic_multicall.aggregate3().await.is_ok());
+
+        // The deadline covers all retries.
+        for _ in 0..3 {
+            push_transport_failure(&asserter, "connection reset");
         }
+        let start = tokio::time::Instant::now();
+        assert!(matches!(
+            dynamic_multicall.aggregate3().await,
+            Err(DynMulticallError::Timeout(t)) if t == timeout
+        ));
+        assert_eq!(start.elapsed(), timeout);
+        assert!(asserter.read_q().is_empty());
 
-        assert_eq!(dynamic_multicall.len(), 40);
+        // The deadline covers all chunks, dropping the ones in flight.
+        push_transport_failure(&asserter, "connection reset");
+        push_transport_failure(&asserter, "connection reset");
+        push_transport_failure(&asserter, "connection reset");
+        let start = tokio::time::Instant::now();
+        assert!(matches!(
+            dynamic_multicall.aggregate3_concurrent(1, 2).await,
+            Err(DynMulticallError::Timeout(_))
+        ));
+        assert_eq!(start.elapsed(), timeout);
+
+        // The deadline covers the other methods making requests.
+        for _ in 0..3 {
+            push_transport_failure(&asserter, "connection reset");
+        }
+        let start = tokio::time::Instant::now();
+        assert!(matches!(
+            dynamic_multicall.get_current_block_timestamp().await,
+            Err(DynMulticallError::Timeout(_))
+        ));
+        assert_eq!(start.elapsed(), timeout);
+
+        for _ in 0..3 {
+            push_transport_failure(&asserter, "connection reset");
+        }
+        let start = tokio::time::Instant::now();
+        assert!(matches!(
+            dynamic_multicall.execute_as_individual_calls().await,
+            Err(DynMulticallError::Timeout(_))
+        ));
+        assert_eq!(start.elapsed(), timeout);
+        assert!(asserter.read_q().is_empty());
+    }
+
+    #[test]
+    fn test_partition_results() {
+        let failure = |idx| Failure {
+            idx,
+            return_data: bytes!("deadbeef"),
+        };
+        let value = |v: u64| vec![DynSolValue::Uint(U256::from(v), 256)];
+
+        let (successes, failures) = partition_results(vec![
+            Ok(value(0)),
+            Err(failure(1)),
+            Err(failure(2)),
+            Ok(value(3)),
+        ]);
+
+        assert_eq!(successes, vec![(0, value(0)), (3, value(3))]);
+        assert_eq!(failures, vec![failure(1), failure(2)]);
+
+        assert_eq!(partition_results(Vec::new()), (Vec::new(), Vec::new()));
+    }
+
+    #[tokio::test]
+    async fn test_retry_failures_individually() {
+        let (asserter, dynamic_multicall) = mock_builder(3, true);
+        let dynamic_multicall = dynamic_multicall.retry_failures_individually(2);
+
+        let result = |success, value: u64| MulticallResult {
+            success,
+            returnData: U256::from(value).to_be_bytes::<32>().into(),
+        };
+        let response = |results: Vec<MulticallResult>| {
+            Bytes::from(aggregate3Call::abi_encode_returns(&results))
+        };
+
+        // The failed call is retried on its own until it succeeds.
+        asserter.push_success(&response(vec![
+            result(true, 0),
+            result(false, 1),
+            result(true, 2),
+        ]));
+        asserter.push_success(&response(vec![result(false, 1)]));
+        asserter.push_success(&response(vec![result(true, 1)]));
 
         let res = dynamic_multicall.aggregate3().await.unwrap();
+        for (idx, result) in res.iter().enumerate() {
+            assert_eq!(
+                result.as_ref().unwrap(),
+                &vec![DynSolValue::Uint(U256::from(idx), 256)]
+            );
+        }
+        assert!(asserter.read_q().is_empty());
 
-        assert_eq!(res.len(), 40);
+        // Calls still failing keep the latest return data.
+        asserter.push_success(&response(vec![
+            result(false, 0),
+            result(true, 1),
+            result(true, 2),
+        ]));
+        asserter.push_success(&response(vec![result(false, 10)]));
+        asserter.push_failure_msg("connection reset");
 
-        for result in res {
-            let decoded = result.unwrap();
-            assert_eq!(decoded.len(), 1);
+        let res = dynamic_multicall.aggregate3().await.unwrap();
+        assert_eq!(
+            res[0],
+            Err(Failure {
+                idx: 0,
+                return_data: U256::from(10).to_be_bytes::<32>().into(),
+            })
+        );
+        assert!(res[1..].iter().all(Result::is_ok));
+        assert!(asserter.read_q().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_revert_fallback() {
+        let (asserter, provider) = mock_provider();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_call(total_supply_call(false))
+            .add_call(
+                DynCallItem::from_signature(
+                    WETH,
+                    "balanceOf(address)(uint256)",
+                    vec![DynSolValue::Address(WETH)],
+                    false,
+                )
+                .unwrap(),
+            );
+
+        let revert = |data: Bytes| {
+            ErrorPayload::internal_error_with_message_and_obj("execution reverted".into(), data)
+                .serialize_payload()
+                .unwrap()
+        };
+
+        // Without the fallback, the revert is returned as is.
+        asserter.push_failure(revert(bytes!("deadbeef")));
+        assert!(matches!(
+            dynamic_multicall.aggregate3().await,
+            Err(DynMulticallError::Multicall(
+                MulticallError::TransportError(_)
+            ))
+        ));
+
+        let dynamic_multicall = dynamic_multicall.with_revert_fallback();
+
+        asserter.push_failure(revert(bytes!("deadbeef")));
+        asserter.push_success(&Bytes::from(U256::from(42).to_be_bytes::<32>()));
+        asserter.push_failure(revert(bytes!("cafebabe")));
+
+        let err = dynamic_multicall.aggregate3().await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "multicall reverted, 1 of 2 calls revert on their own"
+        );
+
+        let DynMulticallError::BatchReverted {
+            revert_data,
+            outcomes,
+        } = err
+        else {
+            panic!("expected a batch revert, got {err:?}");
+        };
+        assert_eq!(revert_data, bytes!("deadbeef"));
+        assert_eq!(
+            outcomes,
+            vec![
+                CallOutcome {
+                    idx: 0,
+                    target: WETH,
+                    function: "totalSupply()".to_string(),
+                    revert_data: None,
+                },
+                CallOutcome {
+                    idx: 1,
+                    target: WETH,
+                    function: "balanceOf(address)".to_string(),
+                    revert_data: Some(bytes!("cafebabe")),
+                },
+            ]
+        );
+        assert!(asserter.read_q().is_empty());
+
+        // Reverts without data, e.g. geth's for a `require` without a message, are reported
+        // with empty revert data.
+        let empty_revert = || ErrorPayload {
+            code: -32000,
+            message: "execution reverted".into(),
+            data: None,
+        };
+        asserter.push_failure(empty_revert());
+        asserter.push_success(&Bytes::from(U256::from(42).to_be_bytes::<32>()));
+        asserter.push_failure(empty_revert());
+
+        let Err(DynMulticallError::BatchReverted {
+            revert_data,
+            outcomes,
+        }) = dynamic_multicall.aggregate3().await
+        else {
+            panic!("expected a batch revert");
+        };
+        assert!(revert_data.is_empty());
+        assert_eq!(outcomes[0].revert_data, None);
+        assert_eq!(outcomes[1].revert_data, Some(Bytes::new()));
+        assert!(asserter.read_q().is_empty());
+
+        // Other errors are returned as is.
+        asserter.push_failure_msg("connection reset");
+        assert!(matches!(
+            dynamic_multicall.aggregate3().await,
+            Err(DynMulticallError::Multicall(
+                MulticallError::TransportError(_)
+            ))
+        ));
+        assert!(asserter.read_q().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_call_stream() {
+        let (asserter, dynamic_multicall) = mock_builder(3, true);
+        let dynamic_multicall = dynamic_multicall.without_block_pinning();
+
+        let result = |value: u64| MulticallResult {
+            success: value != 1,
+            returnData: U256::from(value).to_be_bytes::<32>().into(),
+        };
+
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+            result(0),
+            result(1),
+        ])));
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+            result(2),
+        ])));
+
+        let results = dynamic_multicall
+            .aggregate3_call_stream(2)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        for (position, (idx, result)) in results.into_iter().enumerate() {
+            assert_eq!(idx, position);
+            match result {
+                Ok(values) => assert_eq!(values, vec![DynSolValue::Uint(U256::from(idx), 256)]),
+                Err(failure) => assert_eq!(failure.idx, 1),
+            }
         }
+        assert!(asserter.read_q().is_empty());
+
+        // Chunk errors are yielded as items.
+        asserter.push_failure_msg("connection reset");
+        let mut stream = std::pin::pin!(dynamic_multicall.aggregate3_call_stream(2));
+        assert!(matches!(stream.next().await, Some(Err(_))));
+    }
+
+    #[test]
+    fn test_revert_reason_decode() {
+        let panic = |code: u64| Panic::from(code).abi_encode();
+
+        assert_eq!(
+            RevertReason::decode(&panic(0x01)),
+            RevertReason::Panic(0x01, "assertion failed")
+        );
+        assert_eq!(
+            RevertReason::decode(&panic(0x11)),
+            RevertReason::Panic(0x11, "arithmetic underflow or overflow")
+        );
+        assert_eq!(
+            RevertReason::decode(&panic(0x32)),
+            RevertReason::Panic(0x32, "array out-of-bounds access")
+        );
+        assert_eq!(
+            RevertReason::decode(&panic(0x99)),
+            RevertReason::Panic(0x99, "unknown panic")
+        );
+
+        assert_eq!(
+            RevertReason::decode(&Revert::from("insufficient balance").abi_encode()),
+            RevertReason::Error("insufficient balance".to_string())
+        );
+        assert_eq!(RevertReason::decode(&[]), RevertReason::Empty);
+        assert_eq!(
+            RevertReason::decode(&bytes!("deadbeefcafe")),
+            RevertReason::Custom(FixedBytes::from([0xde, 0xad, 0xbe, 0xef]), bytes!("cafe"))
+        );
+        assert_eq!(
+            RevertReason::decode(&bytes!("dead")),
+            RevertReason::Custom(FixedBytes::from([0xde, 0xad, 0, 0]), Bytes::new())
+        );
     }
 
     #[tokio::test]
-    async fn test_contract_with_fallback() {
-        let _ = tracing_subscriber::fmt::try_init();
+    async fn test_dyn_call_item_new_shared() {
+        let (asserter, provider) = mock_provider();
 
-        let forwarder_contract = address!("0xd5fe1c1f216b775dfd30638fa7164d41321ef79b");
-        let provider = ProviderBuilder::new().connect_anvil_with_config(|a| a.fork(FORK_URL));
+        let balance_of = Arc::new(Function::parse("balanceOf(address)(uint256)").unwrap());
+        let owner = address!("d8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
 
-        let total_supply_function = ERC20::abi::functions()
-            .get("totalSupply")
-            .cloned()
-            .unwrap()
-            .first()
-            .unwrap()
-            .clone();
+        let calls = (1..=3u8)
+            .map(|i| {
+                DynCallItem::new_shared(
+                    Address::with_last_byte(i),
+                    vec![DynSolValue::Address(owner)],
+                    balance_of.clone(),
+                    false,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(Arc::strong_count(&balance_of), 4);
 
-        let balance_of_function = ERC20::abi::functions()
-            .get("balanceOf")
-            .cloned()
-            .unwrap()
-            .first()
+        let dynamic_multicall = DynamicMulticallBuilder::with_calls(provider, calls);
+        assert_eq!(Arc::strong_count(&balance_of), 4);
+
+        let mut expected = bytes!("70a08231").to_vec();
+        expected.extend_from_slice(owner.into_word().as_slice());
+        assert_eq!(
+            dynamic_multicall.encoded_calls().unwrap()[2],
+            (Address::with_last_byte(3), Bytes::from(expected))
+        );
+
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(
+            &(0..3)
+                .map(|i: u64| MulticallResult {
+                    success: true,
+                    returnData: U256::from(i).to_be_bytes::<32>().into(),
+                })
+                .collect::<Vec<_>>(),
+        )));
+        let res = dynamic_multicall.aggregate3().await.unwrap();
+        assert_eq!(
+            res[2].as_ref().unwrap(),
+            &vec![DynSolValue::Uint(U256::from(2), 256)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_with_errors() {
+        let (asserter, provider) = mock_provider();
+
+        let abi = alloy::json_abi::JsonAbi::parse([
+            "error InsufficientBalance(uint256 available, uint256 required)",
+            "error Unauthorized()",
+        ])
+        .unwrap();
+
+        let call_item = total_supply_call(true);
+
+        let dynamic_multicall = DynamicMulticallBuilder::with_calls(provider, vec![call_item; 4])
+            .with_errors(abi.errors().cloned());
+        assert_eq!(dynamic_multicall.errors().len(), 2);
+
+        let insufficient_balance = abi.errors.get("InsufficientBalance").unwrap()[0].clone();
+        let mut insufficient_balance_data = insufficient_balance.selector().to_vec();
+        insufficient_balance_data.extend(
+            DynSolValue::Tuple(vec![
+                DynSolValue::Uint(U256::from(1), 256),
+                DynSolValue::Uint(U256::from(2), 256),
+            ])
+            .abi_encode_params(),
+        );
+
+        let failure = |return_data: Bytes| MulticallResult {
+            success: false,
+            returnData: return_data,
+        };
+
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+            failure(insufficient_balance_data.clone().into()),
+            failure(abi.errors.get("Unauthorized").unwrap()[0].selector().into()),
+            failure(Revert::from("insufficient balance").abi_encode().into()),
+            failure(bytes!("deadbeef")),
+        ])));
+
+        let res = dynamic_multicall.simulate().await.unwrap();
+        assert_eq!(
+            res[0].revert,
+            Some(RevertReason::Decoded(
+                "InsufficientBalance".to_string(),
+                vec![
+                    DynSolValue::Uint(U256::from(1), 256),
+                    DynSolValue::Uint(U256::from(2), 256),
+                ]
+            ))
+        );
+        assert_eq!(
+            res[1].revert,
+            Some(RevertReason::Decoded(
+                "Unauthorized".to_string(),
+                Vec::new()
+            ))
+        );
+        // Standard reverts are decoded first, and unknown selectors are kept raw.
+        assert_eq!(
+            res[2].revert,
+            Some(RevertReason::Error("insufficient balance".to_string()))
+        );
+        assert_eq!(
+            res[3].revert,
+            Some(RevertReason::Custom(
+                FixedBytes::from([0xde, 0xad, 0xbe, 0xef]),
+                Bytes::new()
+            ))
+        );
+
+        // Args that don't match the definition fall back to the raw representation.
+        let truncated = &insufficient_balance_data[..36];
+        assert!(matches!(
+            RevertReason::decode_with_errors(truncated, dynamic_multicall.errors()),
+            RevertReason::Custom(selector, _) if selector == insufficient_balance.selector()
+        ));
+    }
+
+    #[test]
+    fn test_merge() {
+        let (_, provider) = mock_provider();
+        let (_, other_provider) = mock_provider();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .with_block(BlockId::number(1))
+            .add_get_chain_id();
+        let other = DynamicMulticallBuilder::new(other_provider)
+            .with_block(BlockId::number(2))
+            .with_address(Address::with_last_byte(1))
+            .add_get_block_number()
+            .add_get_base_fee();
+
+        let merged = dynamic_multicall.merge(other);
+
+        let names = merged
+            .calls()
+            .iter()
+            .map(|c| c.decoder.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["getChainId", "getBlockNumber", "getBasefee"]);
+
+        // The settings of the merged builder are dropped.
+        assert_eq!(merged.block(), Some(BlockId::number(1)));
+        assert_eq!(merged.address(), MULTICALL3_ADDRESS);
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_decode_error_names_call() {
+        let (asserter, provider) = mock_provider();
+
+        // `totalSupply` actually returns a `uint256`, which isn't a valid array offset.
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_call(
+                DynCallItem::from_signature(WETH, "decimals()(uint8)", Vec::new(), false).unwrap(),
+            )
+            .add_call(
+                DynCallItem::from_signature(WETH, "totalSupply()(uint256[])", Vec::new(), false)
+                    .unwrap(),
+            );
+
+        let return_data: Bytes = U256::from(42).to_be_bytes::<32>().into();
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+            MulticallResult {
+                success: true,
+                returnData: U256::from(18).to_be_bytes::<32>().into(),
+            },
+            MulticallResult {
+                success: true,
+                returnData: return_data.clone(),
+            },
+        ])));
+
+        let err = dynamic_multicall.aggregate3().await.unwrap_err();
+        let message = err.to_string();
+
+        assert!(matches!(
+            &err,
+            DynMulticallError::Decode { idx: 1, target, function, return_data: data, .. }
+                if *target == WETH && function == "totalSupply" && *data == return_data
+        ));
+        assert!(message.contains("call 1"), "{message}");
+        assert!(
+            message.contains("totalSupply() returns (uint256[])"),
+            "{message}"
+        );
+        assert!(message.contains(&return_data.to_string()), "{message}");
+    }
+
+    #[tokio::test]
+    async fn test_aggregate3_detailed() {
+        let owner = address!("d8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
+        let (asserter, provider) = mock_provider();
+
+        let balance_of = DynCallItem::from_signature(
+            WETH,
+            "balanceOf(address)(uint256)",
+            vec![DynSolValue::Address(owner)],
+            true,
+        )
+        .unwrap();
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_call(balance_of.clone())
+            .add_call(balance_of);
+
+        let revert_data = Bytes::from(Revert::from("ERC20: insufficient balance").abi_encode());
+        asserter.push_success(&Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+            MulticallResult {
+                success: true,
+                returnData: U256::from(42).to_be_bytes::<32>().into(),
+            },
+            MulticallResult {
+                success: false,
+                returnData: revert_data.clone(),
+            },
+        ])));
+
+        let res = dynamic_multicall.aggregate3_detailed().await.unwrap();
+        assert_eq!(
+            res[0].as_ref().unwrap()[0],
+            DynSolValue::Uint(U256::from(42), 256)
+        );
+
+        let failure = res[1].clone().unwrap_err();
+        assert_eq!(failure.idx, 1);
+        assert_eq!(failure.target, WETH);
+        assert_eq!(failure.function, "balanceOf");
+        assert_eq!(failure.selector, FixedBytes::from([0x70, 0xa0, 0x82, 0x31]));
+        assert_eq!(
+            failure.reason,
+            Some(RevertReason::Error("ERC20: insufficient balance".into()))
+        );
+        assert_eq!(
+            failure.to_string(),
+            format!("balanceOf({WETH}) at index 1 reverted: ERC20: insufficient balance")
+        );
+
+        assert_eq!(
+            Failure::from(failure),
+            Failure {
+                idx: 1,
+                return_data: revert_data,
+            }
+        );
+    }
+
+    #[test]
+    fn test_revert_reason_display() {
+        assert_eq!(RevertReason::Empty.to_string(), "empty revert");
+        assert_eq!(
+            RevertReason::Panic(0x11, PanicKind::UnderOverflow.as_str()).to_string(),
+            format!("panic 0x11: {}", PanicKind::UnderOverflow.as_str())
+        );
+        assert_eq!(
+            RevertReason::Custom(FixedBytes::from([0x12, 0x34, 0x56, 0x78]), Bytes::new())
+                .to_string(),
+            "custom error 0x12345678"
+        );
+        assert_eq!(
+            RevertReason::Decoded(
+                "InsufficientBalance".into(),
+                vec![
+                    DynSolValue::Uint(U256::from(1), 256),
+                    DynSolValue::String("wei".into())
+                ]
+            )
+            .to_string(),
+            "InsufficientBalance(1, \"wei\")"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_with_call_timeout() {
+        // Connections are queued by the OS, but requests are never answered.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let provider = ProviderBuilder::new().connect_http(url.parse().unwrap());
+
+        let call_item = total_supply_call(false);
+
+        let timeout = Duration::from_millis(100);
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_call(call_item)
+            .with_call_timeout(timeout)
+            .with_retry(1, Duration::ZERO);
+
+        let start = Instant::now();
+        let err = dynamic_multicall.aggregate3().await.unwrap_err();
+
+        let DynMulticallError::Multicall(MulticallError::TransportError(err)) = err else {
+            panic!("expected a transport error, got {err}");
+        };
+        assert_eq!(
+            CallTimeout::from_transport_error(&err),
+            Some(CallTimeout(timeout))
+        );
+        // The timed out `eth_call` was retried once.
+        assert!(start.elapsed() >= timeout * 2);
+
+        drop(listener);
+    }
+
+    #[tokio::test]
+    async fn test_with_retry_covers_all_requests() {
+        let asserter = Asserter::new();
+
+        let call_item = total_supply_call(false);
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(connect_flaky(&asserter))
+            .add_calls(std::iter::repeat_n(call_item, 2))
+            .with_retry(1, Duration::ZERO);
+
+        let value = Bytes::from(U256::from(42).to_be_bytes::<32>());
+        let response = |len| {
+            Bytes::from(aggregate3Call::abi_encode_returns(&vec![
+                MulticallResult {
+                    success: true,
+                    returnData: value.clone(),
+                };
+                len
+            ]))
+        };
+
+        // The batch of individual calls.
+        push_transport_failure(&asserter, "connection reset");
+        asserter.push_success(&value);
+        asserter.push_success(&value);
+        assert!(dynamic_multicall
+            .execute_as_individual_calls()
+            .await
+            .is_ok());
+
+        // The deployless call.
+        push_transport_failure(&asserter, "connection reset");
+        asserter.push_success(&response(2));
+        assert!(dynamic_multicall.aggregate3_deployless().await.is_ok());
+
+        // The block number pinning chunks.
+        push_transport_failure(&asserter, "connection reset");
+        asserter.push_success(&U64::from(100));
+        asserter.push_success(&response(1));
+        asserter.push_success(&response(1));
+        let (block, _) = dynamic_multicall.aggregate3_chunked(1).await.unwrap();
+        assert_eq!(block, Some(100));
+
+        // The re-executions of the revert fallback.
+        let revert = ErrorPayload::internal_error_with_message_and_obj(
+            "execution reverted".into(),
+            bytes!("deadbeef"),
+        )
+        .serialize_payload()
+        .unwrap();
+        asserter.push_failure(revert.clone());
+        push_transport_failure(&asserter, "connection reset");
+        asserter.push_success(&value);
+        asserter.push_failure(revert);
+        assert!(matches!(
+            dynamic_multicall.with_revert_fallback().aggregate3().await,
+            Err(DynMulticallError::BatchReverted { .. })
+        ));
+        assert!(asserter.read_q().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_aggregate3_concurrent_matches_sequential() {
+        let transport = DelayedTransport::default();
+        let provider = ProviderBuilder::new()
+            .connect_client(alloy::rpc::client::RpcClient::new(transport.clone(), true));
+
+        let calls = (1..=40u8).map(|i| {
+            DynCallItem::from_signature(
+                WETH,
+                "balanceOf(address)(uint256)",
+                vec![DynSolValue::Address(Address::with_last_byte(i))],
+                true,
+            )
             .unwrap()
-            .clone();
+        });
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider).add_calls(calls);
 
-        let balance_of_call_item = DynCallItem::new(
-            forwarder_contract,
-            vec![DynSolValue::Address(address!(
-                "d8dA6BF26964aF9D7eEd9e03E53415D37aA96045"
-            ))],
-            balance_of_function,
-            false,
+        let sequential = dynamic_multicall.aggregate3().await.unwrap();
+        assert_eq!(
+            sequential[0],
+            Ok(vec![DynSolValue::Uint(U256::from(1), 256)])
         );
+        assert_eq!(sequential[4].as_ref().unwrap_err().idx, 4);
 
-        let total_supply_call_item =
-            DynCallItem::new(forwarder_contract, Vec::new(), total_supply_function, false);
+        let (chunked_block, chunked) = dynamic_multicall.aggregate3_chunked(4).await.unwrap();
+        assert_eq!(transport.max_in_flight.load(Ordering::SeqCst), 1);
 
-        let dynamic_multicall = DynamicMulticallBuilder::new(provider.clone())
-            .add_call(balance_of_call_item)
-            .add_call(total_supply_call_item);
+        let (block, concurrent) = dynamic_multicall.aggregate3_concurrent(4, 3).await.unwrap();
 
-        assert_eq!(dynamic_multicall.len(), 2);
+        // Later chunks answer first, yet the results match a sequential run.
+        assert_eq!(block, Some(20_000_000));
+        assert_eq!(block, chunked_block);
+        assert_eq!(concurrent, sequential);
+        assert_eq!(concurrent, chunked);
+        assert_eq!(transport.max_in_flight.load(Ordering::SeqCst), 3);
+    }
 
-        let res = dynamic_multicall.aggregate3().await.unwrap();
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_jitter_seed() {
+        let asserter = Asserter::new();
+        let dynamic_multicall = DynamicMulticallBuilder::new(connect_flaky(&asserter))
+            .add_call(total_supply_call(false))
+            .with_retry(2, Duration::from_millis(100))
+            .with_retry_jitter_seed(42);
 
-        assert_eq!(res.len(), 2);
+        push_transport_failure(&asserter, "connection reset");
+        push_transport_failure(&asserter, "connection reset");
+        push_results(&asserter, [(true, 42)]);
 
-        for result in res {
-            let err = result.expect_err("should have errored");
-            assert!(err.return_data.is_empty())
-        }
+        let start = tokio::time::Instant::now();
+        assert!(dynamic_multicall.aggregate3().await.is_ok());
+        // The seeded delays of 87.08ms and 115.99ms, rounded up to the millisecond by the timer.
+        assert_eq!(start.elapsed(), Duration::from_millis(88 + 116));
+    }
+
+    #[test]
+    fn test_clear() {
+        let (_, provider) = mock_provider();
+
+        let dynamic_multicall = DynamicMulticallBuilder::new(provider)
+            .add_call(total_supply_call(false))
+            .with_block(1u64)
+            .with_retry(2, Duration::ZERO)
+            .with_call_gas_limit(1_000_000)
+            .with_strict_decode()
+            .clear();
+
+        assert!(dynamic_multicall.is_empty());
+        assert_eq!(dynamic_multicall.block(), Some(BlockId::number(1)));
+        assert_eq!(dynamic_multicall.retry, Some((2, Duration::ZERO)));
+        assert_eq!(dynamic_multicall.call_gas_limit, Some(1_000_000));
+        assert!(dynamic_multicall.strict_decode);
     }
 }
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use alloy::{
+    dyn_abi::JsonAbiExt,
+    json_abi::Function,
+    primitives::{Address, Bytes, U256},
+};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::DynCallItem;
+
+/// Serialized form of a [`DynCallItem`].
+///
+/// The function is stored as its JSON ABI item and the params ABI-encoded, as
+/// [`DynSolValue`](alloy::dyn_abi::DynSolValue) has no serde representation.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DynCallItemRepr {
+    target: Address,
+    function: Function,
+    params: Bytes,
+    allow_failure: bool,
+    #[serde(default)]
+    value: U256,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+}
+
+impl Serialize for DynCallItem {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let params = self
+            .decoder
+            .abi_encode_input_raw(&self.params)
+            .map_err(ser::Error::custom)?;
+
+        DynCallItemRepr {
+            target: self.target,
+            function: Function::clone(&self.decoder),
+            params: params.into(),
+            allow_failure: self.allow_failure,
+            value: self.value,
+            label: self.label.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DynCallItem {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = DynCallItemRepr::deserialize(deserializer)?;
+
+        let params = repr
+            .function
+            .abi_decode_input(&repr.params)
+            .map_err(de::Error::custom)?;
+
+        Ok(Self {
+            target: repr.target,
+            params,
+            allow_failure: repr.allow_failure,
+            value: repr.value,
+            decoder: Arc::new(repr.function),
+            label: repr.label,
+        })
+    }
+}